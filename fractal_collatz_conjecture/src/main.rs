@@ -1,60 +1,99 @@
-use rusty_fractals_result::palette::Palette;
-use rusty_fractals_core::mem::Mem;
-use rusty_fractals_core::fractal::{FractalConfig, FractalDefinition, Math, MathCollatz};
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier::Square3;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_core::engine::Engine;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, ResultConfig};
 use rusty_fractals_core::mem_collatz::MemCollatz;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier::None;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier::Square3;
+use rusty_fractals_core::viewer;
+use rusty_fractals_domain::domain;
+use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
 use rusty_fractals_result::palettes::palette_blue_to_white;
-use log::{info};
-
-const NAME: &str = "Collatz Conjecture";
-const ITERATION_MAX: u32 = 14800;
-const ITERATION_MIN: u32 = 42;
-const AREA_SIZE: f64 = 7.0;
-const TARGET_RE: f64 = -0.088485445553580480;
-const TARGET_IM: f64 = -0.200679435068532800;
-const RESOLUTION_WIDTH: u32 = 1280;
-const RESOLUTION_HEIGHT: u32 = 720;
-const RESOLUTION_MULTIPLIER: ResolutionMultiplier = Square3;
-const REPEAT: bool = true;
-const SAVE_IMAGES: bool = false;
-const PALETTE: Palette = palette_blue_to_white();
-
-struct CollatzConjecture {
-    pub name: String,
-}
 
-impl MathCollatz for CollatzConjecture {
+struct CollatzConjecture {}
+
+impl Math<MemCollatz> for CollatzConjecture {
     fn math(&self, m: &mut MemCollatz, origin_re: f64, origin_im: f64) {
         m.square();
         m.collatz_conjecture();
         m.plus(origin_re, origin_im);
     }
+
+    // the first iteration is odd
+    fn new_mem(&self, origin_re: f64, origin_im: f64) -> MemCollatz {
+        MemCollatz::init(origin_re, origin_im, 1)
+    }
 }
 
 fn main() {
-    info!("Started");
+    let name = "Collatz Conjecture";
 
-    let collatz = CollatzConjecture { name: NAME.to_string() };
-    let definition = FractalDefinition { iteration_min: ITERATION_MIN, iteration_max: ITERATION_MAX, area_size: AREA_SIZE, target_re: TARGET_RE, target_im: TARGET_IM };
-    let config = FractalConfig { resolution_width: RESOLUTION_WIDTH, resolution_height: RESOLUTION_HEIGHT, resolution_multiplier: RESOLUTION_MULTIPLIER, repeat: REPEAT, save_images: SAVE_IMAGES, palette: PALETTE };
+    let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: Divergent,
+        iteration_min: 42,
+        iteration_max: 14800,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: true,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1500,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    let area_cfg = area::AreaConfig {
+        width_re: 7.0,
+        center_re: -0.088485445553580480,
+        center_im: -0.200679435068532800,
+        width_x: 1280,
+        height_y: 720,
+        rotation: 0.0,
+    };
+    let result_config = ResultConfig {
+        palette: palette_blue_to_white(),
+        palette_zero: None,
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
+    };
 
-    info!("Fractal {}", collatz.name);
+    println!("Fractal {}", name);
 
-    let m = Mem { re: 0.0, im: 0.0 };
-    let mut mc = MemCollatz { m, it: 1 };
-    collatz.math(&mut mc, 1.0, 0.1);
+    let collatz = CollatzConjecture {};
+    let mut area = area::init(area_cfg);
+    let mut result_area = area::init(area_cfg);
+    let mut domain = domain::init(&area, Square3);
+    let viewer = viewer::init_window(name, result_area.width_x, result_area.height_y);
+    let mut engine = Engine {
+        area: &mut area,
+        result_area: &mut result_area,
+        domain: &mut domain,
+        calculation_config,
+        app_config,
+        result_config,
+        viewer,
+    };
 
-    info!("Finished.");
+    if let Err(e) = engine.calculate(&collatz) {
+        println!("Failed to save images: {}", e);
+    }
+
+    println!("Finished.");
 }
 
 #[test]
 fn test_math() {
-    let collatz = CollatzConjecture { name: NAME.to_string() };
-    let m = Mem { re: 0.0, im: 0.0 };
-    let mut mc = MemCollatz { m, it: 1 };
+    use rusty_fractals_core::fractal::MemType;
+
+    let collatz = CollatzConjecture {};
+    let mut mc = collatz.new_mem(0.0, 0.0);
     collatz.math(&mut mc, 1.0, 0.1);
     assert_eq!(mc.re(), 2.0);
     assert_eq!(mc.im(), 1.1);
-}
\ No newline at end of file
+}
@@ -1,18 +1,22 @@
-use rusty_fractals_core::fractal::{FractalConfig, FractalDefinition, MathCollatz};
-use rusty_fractals_core::mem::Mem;
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier::None;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_core::engine::Engine;
+use rusty_fractals_core::fractal::FractalType::Mandelbrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, ResultConfig};
 use rusty_fractals_core::mem_collatz::MemCollatz;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier::None;
-use rusty_fractals_result::palettes::palette_gray_to_blue;
-use log::{info};
+use rusty_fractals_core::viewer;
+use rusty_fractals_domain::domain;
+use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
+use rusty_fractals_result::palettes::{palette_black_to_gray, palette_gray_to_blue};
 
-const NAME: &str = "Collatz Conjecture Mandelbrot";
+struct CollatzConjectureMandelbrot {}
 
-struct CollatzConjectureMandelbrot {
-    pub name: String,
-}
-
-impl MathCollatz for CollatzConjectureMandelbrot {
+impl Math<MemCollatz> for CollatzConjectureMandelbrot {
     fn math(&self, mc: &mut MemCollatz, origin_re: f64, origin_im: f64) {
         mc.m.square();
         mc.plus_collatz(origin_re, origin_im);
@@ -20,38 +24,71 @@ impl MathCollatz for CollatzConjectureMandelbrot {
 }
 
 fn main() {
-    info!("Started");
+    let name = "Collatz Conjecture Mandelbrot";
 
-    let collatz = CollatzConjectureMandelbrot { name: NAME.to_string() };
-    let definition = FractalDefinition {
+    let calculation_config = CalculationConfig {
+        fractal_type: Mandelbrot,
+        path_selection: Divergent,
         iteration_min: 0,
         iteration_max: 14800,
-        area_size:  3.0,
-        target_re: -0.882952991714172300,
-        target_im: -0.214699221335319460,
-        resolution_width: 1280,
-        resolution_height: 720,
-        resolution_multiplier: None,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
         repeat: true,
         save_images: false,
-        palette: palette_gray_to_blue()
+        output_dir: constants::PATH.to_string(),
+        frames: 1500,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    let area_cfg = area::AreaConfig {
+        width_re: 3.0,
+        center_re: -0.882952991714172300,
+        center_im: -0.214699221335319460,
+        width_x: 1280,
+        height_y: 720,
+        rotation: 0.0,
+    };
+    let result_config = ResultConfig {
+        palette: palette_gray_to_blue(),
+        palette_zero: Some(palette_black_to_gray()),
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
     };
 
-    info!("Fractal {}", collatz.name);
+    println!("Fractal {}", name);
 
-    let m = Mem { re: 0.0, im: 0.0 };
-    let mut mc = MemCollatz { m, it: 0 };
-    collatz.math(&mut mc, 1.0, 0.1);
+    let collatz = CollatzConjectureMandelbrot {};
+    let mut area = area::init(area_cfg);
+    let mut result_area = area::init(area_cfg);
+    let mut domain = domain::init(&area, None);
+    let viewer = viewer::init_window(name, result_area.width_x, result_area.height_y);
+    let mut engine = Engine {
+        area: &mut area,
+        result_area: &mut result_area,
+        domain: &mut domain,
+        calculation_config,
+        app_config,
+        result_config,
+        viewer,
+    };
 
-    info!("Finished.");
-}
+    if let Err(e) = engine.calculate(&collatz) {
+        println!("Failed to save images: {}", e);
+    }
 
+    println!("Finished.");
+}
 
 #[test]
 fn test_math() {
-    let collatz = CollatzConjectureMandelbrot { name: NAME.to_string() };
+    use rusty_fractals_core::fractal::MemType;
+    use rusty_fractals_core::mem::Mem;
+
+    let collatz = CollatzConjectureMandelbrot {};
     let m = Mem { re: 0.0, im: 0.0 };
-    let mut mc = MemCollatz { m, it: 0 };
+    let mut mc = MemCollatz { m, it: 0, it_initial: 0 };
     collatz.math(&mut mc, 1.0, 0.1);
     assert_eq!(mc.re(), 2.0);
     assert_eq!(mc.im(), 0.65);
@@ -1,7 +1,6 @@
-use rusty_fractals_core::fractal::CALCULATION_BOUNDARY;
+use rusty_fractals_core::fractal::Math;
 use rusty_fractals_core::mathematician::Mathematician;
-use rusty_fractals_domain::domain_element::DomainElement;
-use log::{debug};
+use rusty_fractals_core::mem_euler::{MemEuler, Spectra};
 
 // Fractal Euler type uses three color spectra for better mathematical analysis and better coloring results.
 // Possible use as:
@@ -9,85 +8,37 @@ use log::{debug};
 // - Fibonacci path lengths / el.order -> Green spectrum
 // - other path lengths / el.order     -> Blue spectrum
 
-fn add_calculation_path(path: Vec<[i64; 2]>) {
-    paths.add(path);
+pub struct Euler {
+    pub math: Mathematician,
 }
 
-fn translate_paths_to_pixel_grid() {
-    debug!("translate_paths_to_pixel_grid");
-
-    let mut added = 0;
-    for path in paths {
-        for i in 0..path.size() - 1 {
-            let tmp = path.get(i);
-            // translate [re,im] to [px,py]
-            AreaFinebrot.pointToPixel(m, tmp[0], tmp[1]);
-            if m.good {
-                added += 1;
-                FractalEuler.colorsFor(m, i, path.size());
-                PixelsEulerFinebrot.add(m.px, m.py, m.spectra);
-            }
-        }
+impl Math<MemEuler> for Euler {
+    fn math(&self, mp: &mut MemEuler, origin_re: f64, origin_im: f64) {
+        mp.m.square();
+        mp.m.plus(origin_re, origin_im);
+        mp.euler(&self.math);
+        mp.m.square();
+        mp.m.plus(origin_re, origin_im);
     }
-    debug!("* Added:   {}", added);
-
-    /* remove elements which moved ouf of tiny area */
-    removeElementsOutside();
 }
 
-fn colors_for(m: MemEuler, element_index: u32, path_length: u32) {
-    if Mathematician.isPrime(element_index) {
-        m.spectra = red;
-        return;
+pub fn colors_for(math: &Mathematician, element_index: u32, path_length: u32) -> Spectra {
+    if math.is_prime(&element_index) {
+        return Spectra::Red;
     }
-    if Mathematician.isPrime(path_length) {
-        m.spectra = green;
-        return;
+    if math.is_prime(&path_length) {
+        return Spectra::Green;
     }
-    m.spectra = blue;
+    Spectra::Blue
 }
 
-fn calculate_path(el: &DomainElement) {
-    /*
-    let mut iterator = 0;
-    let length = 0;
-    final MemEuler
-    m = new
-    MemEuler(el.origin_re, el.origin_im);
-    while m.quad() < CALCULATION_BOUNDARY && iterator < ITERATION_MAX {
-        /*
-         * Investigate if this is a good calculation path
-         * Don't create path data yet. Too many origin's don't produce good data
-         * Most long expensive calculations end up inside Mandelbrot set
-         */
-        math(m, el.origin_re, el.origin_im);
-        if AreaFinebrot.contains(m) {
-            length += 1;
-        }
-        iterator += 1;
-    }
+#[test]
+fn test_colors_for() {
+    use std::collections::HashSet;
 
-    /* Verify divergent path length */
-    if length > ITERATION_min && iterator < ITERATION_MAX {
-        /*
-         * This origin produced good data, record calculation path
-         */
-        m.reset(el.origin_re, el.origin_im);
-        el.goodPath();
-        final ArrayList < double
-        [] > path = new
-        ArrayList < > (length);
-        for i in 0..iterator {
-            math(m, el.origin_re, el.origin_im);
-            if AreaFinebrot.contains(m) {
-                path.add(new double[]{ m.re, m.im });
-            }
-        }
-        el.set_finished_state(iterator, length);
-        return path;
-    } else {
-        el.set_finished_state(iterator, length);
-        return null;
-    }
-    */
+    let math = Mathematician { primes: HashSet::from([3, 5]), fibonacci: HashSet::new(), perfect: HashSet::new(), square: HashSet::new() };
+
+    assert_eq!(colors_for(&math, 3, 4), Spectra::Red);
+    assert_eq!(colors_for(&math, 4, 5), Spectra::Green);
+    assert_eq!(colors_for(&math, 4, 6), Spectra::Blue);
 }
@@ -1,83 +1,127 @@
 mod euler;
-mod pixel;
-mod mandelbrot;
 
-use rusty_fractals_core::fractal::{FractalConfig, FractalDefinition, MathEuler};
-use rusty_fractals_core::mem::Mem;
-use rusty_fractals_core::mem_euler::{MemEuler, Spectra};
-use rysty_fractals_core::mathematician::Mathematician;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier::None;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
-use rusty_fractals_result::palette::Palette;
-use rusty_fractals_result::palettes::PALETTE_3_RGB;
+use std::sync::Arc;
+use std::collections::HashSet;
+use resolution_multiplier::ResolutionMultiplier;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, Band, CalculationConfig, ResultConfig};
+use rusty_fractals_core::machine::Machine;
+use rusty_fractals_core::mathematician;
 use rusty_fractals_core::mathematician::Mathematician;
-use crate::pixel::Spectra;
-
-const NAME: &str = "Euler";
-
-const ITERATION_MAX: u32 = 80000;
-const ITERATION_MIN: u32 = 42;
-const AREA_SIZE: f64 = 4.0;
-const TARGET_RE: f64 = 0.0;
-const TARGET_IM: f64 = 0.0;
-const RESOLUTION_WIDTH: u32 = 1920;
-const RESOLUTION_HEIGHT: u32 = 1080;
-const RESOLUTION_MULTIPLIER: ResolutionMultiplier = None;
-const REPEAT: bool = false;
-const SAVE_IMAGES: bool = false;
-const PALETTE: Palette = PALETTE_3_RGB;
-
-struct Euler {
-    pub name: String,
-}
-
-impl MathEuler for Euler {
-    fn math(&self, mp: &mut MemEuler, origin_re: f64, origin_im: f64) {
-        mp.m.square();
-        mp.m.plus(origin_re, origin_im);
-        mp.euler();
-        mp.m.square();
-        mp.m.plus(origin_re, origin_im);
-    }
-}
+use rusty_fractals_core::{fractal_stats, image_writer};
+use rusty_fractals_domain::domain;
+use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::palette::Palette;
+use rusty_fractals_result::result_pixels;
+use rusty_fractals_result::result_pixels::ResultPixels;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
+use rusty_fractals_result::palettes::{palette_black_to_blue, palette_black_to_green, palette_black_to_red, palette_black_to_white};
+use crate::euler::{colors_for, Euler};
 
 fn main() {
-    info!("Started");
+    let name = "Euler";
 
-    let euler = Euler { name: NAME.to_string() };
-    let definition = FractalDefinition { iteration_min: ITERATION_MIN, iteration_max: ITERATION_MAX, area_size: AREA_SIZE, target_re: TARGET_RE, target_im: TARGET_IM };
-    let config = FractalConfig { resolution_width: RESOLUTION_WIDTH, resolution_height: RESOLUTION_HEIGHT, resolution_multiplier: RESOLUTION_MULTIPLIER, repeat: REPEAT, save_images: SAVE_IMAGES, palette: PALETTE };
+    let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: Divergent,
+        iteration_min: 42,
+        iteration_max: 80000,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: false,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    let area_cfg = area::AreaConfig {
+        width_re: 4.0,
+        center_re: 0.0,
+        center_im: 0.0,
+        width_x: 1920,
+        height_y: 1080,
+        rotation: 0.0,
+    };
+    // Euler spectra are layers colored like Nebulabrot bands, in order of Spectra, palette isn't used
+    // paths of any length go to each band, spectrum of the path decides its layer
+    let all = |palette| Band { iteration_from: calculation_config.iteration_min, iteration_to: calculation_config.iteration_max + 1, palette };
+    let result_config = ResultConfig {
+        palette: palette_black_to_white(),
+        palette_zero: None,
+        bands: vec![all(palette_black_to_red()), all(palette_black_to_green()), all(palette_black_to_blue())],
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
+    };
 
-    info!("Fractal {}", euler.name);
+    println!("Fractal {}", name);
 
-    let m = Mem { re: 0.0, im: 0.0 };
-    let math = Mathematician {
-        primes: Mathematician::init_primes(),
-        fibonacci: Default::default(),
-        perfect: Default::default(),
-        square: Default::default(),
+    // path length and element index are never more than iteration_max
+    let euler = Euler {
+        math: Mathematician {
+            primes: mathematician::init_primes(calculation_config.iteration_max + 1),
+            fibonacci: HashSet::new(),
+            perfect: HashSet::new(),
+            square: HashSet::new(),
+        },
+    };
+    let area = area::init(area_cfg);
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = Machine {
+        area: &area,
+        result_area: &area,
+        domain: &domain,
+        calculation_config,
+        app_config: app_config.clone(),
+        result_config,
     };
-    let mut me = MemEuler { m, it: 0, math, spectra: Spectra::Red }; // TODO
 
-    euler.math(&mut me, 1.0, 0.1);
+    // single image, points are added right away to the layer of their spectrum, no paths are kept
+    let layers: Vec<ResultPixels> = machine.result_config.bands.iter()
+        .map(|_| {
+            let mut layer = result_pixels::init(area.width_x, area.height_y);
+            layer.splatting = machine.result_config.splatting;
+            layer
+        })
+        .collect();
+    let spectrum_of = |index, length| colors_for(&euler.math, index, length) as usize;
+    machine.calculate_spectra(&euler, &layers, &spectrum_of, &mut fractal_stats::init());
 
-    info!("Finished.");
+    let palettes: Vec<&Palette> = machine.result_config.bands.iter().map(|band| &band.palette).collect();
+    let result_image = coloring_strategy::color_layers(machine.result_config.coloring.as_ref(), &layers, &palettes);
+
+    if app_config.save_images {
+        if let Err(e) = image_writer::init(&app_config).save_result_image(&result_image, 1) {
+            println!("Failed to save images: {}", e);
+        }
+    }
+
+    println!("Finished.");
 }
 
 #[test]
 fn test_math() {
-    let euler = Euler { name: NAME.to_string() };
-    let m = Mem { re: 0.0, im: 0.0 };
-    let math = Mathematician {
-        primes: Mathematician::init_primes(),
-        fibonacci: Default::default(),
-        perfect: Default::default(),
-        square: Default::default()
+    use rusty_fractals_core::fractal::{Math, MemType};
+    use rusty_fractals_core::mem_euler::MemEuler;
+
+    let euler = Euler {
+        math: Mathematician {
+            primes: mathematician::init_primes(100),
+            fibonacci: Default::default(),
+            perfect: Default::default(),
+            square: Default::default(),
+        },
     };
-    let mut me = MemEuler { m, it: 0, math, spectra: Spectra::Red }; // TODO
+    let mut me = MemEuler::new(0.0, 0.0);
 
     euler.math(&mut me, 1.0, 0.1);
 
     assert_eq!(me.re(), 0.9901);
     assert_eq!(me.im(), 0.10200000000000001);
-}
\ No newline at end of file
+}
@@ -1,7 +0,0 @@
-use log::{debug, info};
-
-pub fn update() {
-    debug!("update()");
-    ITERATION_MAX += 150;
-    info!("ITERATION_MAX = {}", ITERATION_MAX);
-}
\ No newline at end of file
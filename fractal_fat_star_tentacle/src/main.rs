@@ -1,35 +1,21 @@
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, ResultConfig};
+use rusty_fractals_core::machine::Machine;
 use rusty_fractals_core::mem::Mem;
-use rusty_fractals_core::fractal::{FractalConfig, FractalDefinition, Math};
+use rusty_fractals_domain::domain;
 use rusty_fractals_domain::resolution_multiplier;
-use rusty_fractals_result::palette::Palette;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
 use rusty_fractals_result::palettes::palette_black_to_white;
-use resolution_multiplier::ResolutionMultiplier;
-use resolution_multiplier::ResolutionMultiplier::None;
-use log::{info};
-
-const NAME: &str = "Fat Star Tentacle";
 
-const ITERATION_MAX: u32 = 81_000;
-const ITERATION_MIN: u32 = 8;
-const AREA_SIZE: f64 = 3.5;
-const TARGET_RE: f64 = 0.0;
-const TARGET_IM: f64 = 0.0;
-// TODO
-// const INIT_FINEBROT_AREA_SIZE : f64= 0.5;
-// const INIT_FINEBROT_TARGET_re : f64= 0.5;
-// const INIT_FINEBROT_TARGET_im : f64= -0.38;
-const RESOLUTION_WIDTH: u32 = 1920;
-const RESOLUTION_HEIGHT: u32 = 1080;
-const RESOLUTION_MULTIPLIER: ResolutionMultiplier = None;
-const REPEAT: bool = false;
-const SAVE_IMAGES: bool = false;
-const PALETTE: Palette = palette_black_to_white();
-
-struct FatStarTentacle {
-    pub name: String,
-}
+struct FatStarTentacle {}
 
-impl Math for FatStarTentacle {
+impl Math<Mem> for FatStarTentacle {
     fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
         m.square();
         m.conjugation();
@@ -39,24 +25,74 @@ impl Math for FatStarTentacle {
 }
 
 fn main() {
-    info!("Started");
+    let name = "Fat Star Tentacle";
 
-    let fat_star_tentacle = FatStarTentacle { name: NAME.to_string() };
-    let definition = FractalDefinition { iteration_min: ITERATION_MIN, iteration_max: ITERATION_MAX, area_size: AREA_SIZE, target_re: TARGET_RE, target_im: TARGET_IM };
-    let config = FractalConfig { resolution_width: RESOLUTION_WIDTH, resolution_height: RESOLUTION_HEIGHT, resolution_multiplier: RESOLUTION_MULTIPLIER, repeat: REPEAT, save_images: SAVE_IMAGES, palette: PALETTE };
+    let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: Divergent,
+        iteration_min: 8,
+        iteration_max: 81_000,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: false,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    // origins of paths are seeded over the whole fractal
+    let domain_area_cfg = area::AreaConfig {
+        width_re: 3.5,
+        center_re: 0.0,
+        center_im: 0.0,
+        width_x: 1920,
+        height_y: 1080,
+        rotation: 0.0,
+    };
+    // only the detail is displayed
+    let result_area_cfg = area::AreaConfig {
+        width_re: 0.5,
+        center_re: 0.5,
+        center_im: -0.38,
+        width_x: 1920,
+        height_y: 1080,
+        rotation: 0.0,
+    };
+    let result_config = ResultConfig {
+        palette: palette_black_to_white(),
+        palette_zero: None,
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
+    };
 
-    info!("Fractal {}", fat_star_tentacle.name);
+    println!("Fractal {}", name);
 
-    let mut m = Mem { re: 0.0, im: 0.0 };
-    fat_star_tentacle.math(&mut m, 1.0, 0.1);
+    let fat_star_tentacle = FatStarTentacle {};
+    let domain_area = area::init(domain_area_cfg);
+    let result_area = area::init(result_area_cfg);
+    let domain = domain::init(&domain_area, ResolutionMultiplier::None);
+    let mut machine = Machine {
+        area: &domain_area,
+        result_area: &result_area,
+        domain: &domain,
+        calculation_config,
+        app_config,
+        result_config,
+    };
 
-    info!("Finished.");
-}
+    if let Err(e) = machine.calculate(&fat_star_tentacle) {
+        println!("Failed to save images: {}", e);
+    }
 
+    println!("Finished.");
+}
 
 #[test]
 fn test_math() {
-    let fat_star_tentacle = FatStarTentacle { name: NAME.to_string() };
+    let fat_star_tentacle = FatStarTentacle {};
     let mut m = Mem { re: 0.0, im: 0.0 };
     fat_star_tentacle.math(&mut m, 1.0, 0.1);
     assert_eq!(m.re, 1.0);
@@ -1,32 +1,26 @@
-use rusty_fractals_core::fractal::{FractalConfig, FractalDefinition, Math, MathPhoenix};
-use rusty_fractals_core::mem::Mem;
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier::Square5;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_core::engine::Engine;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, ResultConfig};
 use rusty_fractals_core::mem_phoenix::MemPhoenix;
+use rusty_fractals_core::viewer;
+use rusty_fractals_domain::domain;
 use rusty_fractals_domain::resolution_multiplier;
-use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier::Square5;
-use resolution_multiplier::ResolutionMultiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
 use rusty_fractals_result::palettes::palette_blue_to_white;
 
-use log::{info};
-const NAME: &str = "Glorious Head";
 const PHOENIX_INIT_C: f64 = 0.35;
 const PHOENIX_INIT_P: f64 = -0.25;
 const PHOENIX_INIT_PHOENIX_INITIALIZER: f64 = 1.0;
-const ITERATION_MAX: u32 = 2500;
-const ITERATION_MIN: u32 = 8;
-const AREA_SIZE: f64 = 4.5;
-const TARGET_RE: f64 = -0.16884290496519;
-const TARGET_IM: f64 = -0.37573460559804;
-const RESOLUTION_WIDTH: u32 = 1280;
-const RESOLUTION_HEIGHT: u32 = 720;
-const RESOLUTION_MULTIPLIER: ResolutionMultiplier = Square5;
-const REPEAT: bool = true;
-const SAVE_IMAGES: bool = false;
-
-struct GloriousHead {
-    pub name: String,
-}
 
-impl MathPhoenix for GloriousHead {
+struct GloriousHead {}
+
+impl Math<MemPhoenix> for GloriousHead {
     fn math(&self, mp: &mut MemPhoenix, origin_re: f64, origin_im: f64) {
         mp.m.square();
 
@@ -42,31 +36,76 @@ impl MathPhoenix for GloriousHead {
 
         mp.m.plus(origin_re, origin_im);
     }
+
+    fn new_mem(&self, origin_re: f64, origin_im: f64) -> MemPhoenix {
+        MemPhoenix::init(origin_re, origin_im, PHOENIX_INIT_C, PHOENIX_INIT_P, PHOENIX_INIT_PHOENIX_INITIALIZER)
+    }
 }
 
 fn main() {
-    info!("Started");
-
-
-    let glorious_head = GloriousHead { name: NAME.to_string() };
-    let definition = FractalDefinition { iteration_min: ITERATION_MIN, iteration_max: ITERATION_MAX, area_size: AREA_SIZE, target_re: TARGET_RE, target_im: TARGET_IM };
-    let config = FractalConfig { resolution_width: RESOLUTION_WIDTH, resolution_height: RESOLUTION_HEIGHT, resolution_multiplier: RESOLUTION_MULTIPLIER, repeat: REPEAT, save_images: SAVE_IMAGES, palette: PALETTE };
-
-    info!("Fractal {}", glorious_head.name);
-
-    let m = Mem { re: 0.0, im: 0.0 };
-    let mut mp = MemPhoenix { m, c: PHOENIX_INIT_C, p: PHOENIX_INIT_P, prev_prev_re: PHOENIX_INIT_PHOENIX_INITIALIZER, prev_prev_im: PHOENIX_INIT_PHOENIX_INITIALIZER, prev_re: PHOENIX_INIT_PHOENIX_INITIALIZER, prev_im: PHOENIX_INIT_PHOENIX_INITIALIZER };
-
-    glorious_head.math(&mut mp, 1.0, 0.1);
+    let name = "Glorious Head";
+
+    let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: Divergent,
+        iteration_min: 8,
+        iteration_max: 2500,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: true,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1500,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    let area_cfg = area::AreaConfig {
+        width_re: 4.5,
+        center_re: -0.16884290496519,
+        center_im: -0.37573460559804,
+        width_x: 1280,
+        height_y: 720,
+        rotation: 0.0,
+    };
+    let result_config = ResultConfig {
+        palette: palette_blue_to_white(),
+        palette_zero: None,
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
+    };
+
+    println!("Fractal {}", name);
+
+    let glorious_head = GloriousHead {};
+    let mut area = area::init(area_cfg);
+    let mut result_area = area::init(area_cfg);
+    let mut domain = domain::init(&area, Square5);
+    let viewer = viewer::init_window(name, result_area.width_x, result_area.height_y);
+    let mut engine = Engine {
+        area: &mut area,
+        result_area: &mut result_area,
+        domain: &mut domain,
+        calculation_config,
+        app_config,
+        result_config,
+        viewer,
+    };
+
+    if let Err(e) = engine.calculate(&glorious_head) {
+        println!("Failed to save images: {}", e);
+    }
 
-    info!("Finished.");
+    println!("Finished.");
 }
 
 #[test]
 fn test_math() {
-    let glorious_head = GloriousHead { name: NAME.to_string() };
-    let m = Mem { re: 0.0, im: 0.0 };
-    let mut mp = MemPhoenix { m, c: PHOENIX_INIT_C, p: PHOENIX_INIT_P, prev_prev_re: PHOENIX_INIT_PHOENIX_INITIALIZER, prev_prev_im: PHOENIX_INIT_PHOENIX_INITIALIZER, prev_re: PHOENIX_INIT_PHOENIX_INITIALIZER, prev_im: PHOENIX_INIT_PHOENIX_INITIALIZER };
+    use rusty_fractals_core::fractal::MemType;
+
+    let glorious_head = GloriousHead {};
+    let mut mp = glorious_head.new_mem(0.0, 0.0);
 
     glorious_head.math(&mut mp, 1.0, 0.1);
 
@@ -1,57 +1,91 @@
-mod infinity;
-
-use rusty_fractals_result::palette::Palette;
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::NonDivergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, ResultConfig};
+use rusty_fractals_core::machine::Machine;
 use rusty_fractals_core::mem::Mem;
-use rusty_fractals_core::fractal::{FractalConfig, FractalDefinition, Math};
+use rusty_fractals_domain::domain;
 use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
 use rusty_fractals_result::palettes::palette_black_to_white;
-use resolution_multiplier::ResolutionMultiplier;
-use resolution_multiplier::ResolutionMultiplier::None;
-use log::{info};
-
-const NAME: &str = "Infinity";
-const ITERATION_MAX: u32 = 180_000;
-const ITERATION_MIN: u32 = 3000;
-const AREA_SIZE: f64 = 2.6;
-const TARGET_RE: f64 = -0.5;
-const TARGET_IM: f64 = 0.0;
-const RESOLUTION_WIDTH: u32 = 1920;
-const RESOLUTION_HEIGHT: u32 = 1080;
-const RESOLUTION_MULTIPLIER: ResolutionMultiplier = None;
-const REPEAT: bool = false;
-const SAVE_IMAGES: bool = false;
-const PALETTE: Palette = palette_black_to_white();
-
-struct Infinity {
-    pub name: String,
-}
 
-impl Math for Infinity {
+// Paths of origins inside of Mandelbrot set, which never diverge
+struct Infinity {}
+
+impl Math<Mem> for Infinity {
     fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
         m.square();
         m.plus(origin_re, origin_im);
     }
+
+    // deep zoom
+    fn perturbation(&self) -> bool {
+        true
+    }
 }
 
 fn main() {
-    info!("Started");
+    let name = "Infinity";
 
-    let infinity = Infinity { name: NAME.to_string() };
-    let definition = FractalDefinition { iteration_min: ITERATION_MIN, iteration_max: ITERATION_MAX, area_size: AREA_SIZE, target_re: TARGET_RE, target_im: TARGET_IM };
-    let config = FractalConfig { resolution_width: RESOLUTION_WIDTH, resolution_height: RESOLUTION_HEIGHT, resolution_multiplier: RESOLUTION_MULTIPLIER, repeat: REPEAT, save_images: SAVE_IMAGES, palette: PALETTE };
+    let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: NonDivergent,
+        iteration_min: 3000,
+        iteration_max: 180_000,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: false,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    let area_cfg = area::AreaConfig {
+        width_re: 2.6,
+        center_re: -0.5,
+        center_im: 0.0,
+        width_x: 1920,
+        height_y: 1080,
+        rotation: 0.0,
+    };
+    let result_config = ResultConfig {
+        palette: palette_black_to_white(),
+        palette_zero: None,
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
+    };
 
-    info!("Fractal {}", infinity.name);
+    println!("Fractal {}", name);
 
-    let mut m = Mem { re: 0.0, im: 0.0 };
-    infinity.math(&mut m, 1.0, 0.1);
+    let infinity = Infinity {};
+    let domain_area = area::init(area_cfg);
+    let domain = domain::init(&domain_area, ResolutionMultiplier::None);
+    let mut machine = Machine {
+        area: &domain_area,
+        result_area: &domain_area,
+        domain: &domain,
+        calculation_config,
+        app_config,
+        result_config,
+    };
 
-    info!("Finished.");
-}
+    if let Err(e) = machine.calculate(&infinity) {
+        println!("Failed to save images: {}", e);
+    }
 
+    println!("Finished.");
+}
 
 #[test]
 fn test_math() {
-    let infinity = Infinity { name: NAME.to_string() };
+    let infinity = Infinity {};
     let mut m = Mem { re: 0.0, im: 0.0 };
     infinity.math(&mut m, 1.0, 0.1);
     assert_eq!(m.re, 1.0);
@@ -1,10 +1,18 @@
+use std::sync::Arc;
 use resolution_multiplier::ResolutionMultiplier::SquareAlter;
 use rusty_fractals_common::area;
-use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, ResultConfig};
+use rusty_fractals_common::constants;
+use rusty_fractals_common::double_double::DoubleDouble;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, MathDD, ResultConfig};
 use rusty_fractals_core::machine::Machine;
 use rusty_fractals_core::mem::Mem;
-use rusty_fractals_domain::domain::{init_domain_elements, Domain};
+use rusty_fractals_core::mem_dd::MemDD;
+use rusty_fractals_domain::domain;
 use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
 use rusty_fractals_result::palettes::palette_blue_to_white;
 
 struct Nebula {}
@@ -14,18 +22,41 @@ impl Math<Mem> for Nebula {
         m.square();
         m.plus(origin_re, origin_im);
     }
+
+    // deep zoom
+    fn double_double(&self) -> Option<&dyn MathDD> {
+        Some(self)
+    }
+
+    fn perturbation(&self) -> bool {
+        true
+    }
+}
+
+impl MathDD for Nebula {
+    fn math_dd(&self, m: &mut MemDD, origin_re: DoubleDouble, origin_im: DoubleDouble) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
 }
 
 fn main() {
     let name = "Nebula";
 
     let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: Divergent,
         iteration_min: 42,
         iteration_max: 14800,
     };
     let app_config = AppConfig {
+        name: name.to_string(),
         repeat: false,
         save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1,
+        width_re_min: 0.0,
+        rotation: 0.0,
     };
     let area_cfg = area::AreaConfig {
         width_re: 7.0,
@@ -33,31 +64,33 @@ fn main() {
         center_im: 0.0,
         width_x: 1280,
         height_y: 720,
+        rotation: 0.0,
     };
     let result_config = ResultConfig {
         palette: palette_blue_to_white(),
+        palette_zero: None,
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
     };
 
     println!("Fractal {}", name);
 
     let nebula = Nebula {};
     let domain_area = area::init(area_cfg);
-    let domain = Domain {
-        width: domain_area.width_x,
-        height: domain_area.height_y,
-        domain_area: &domain_area,
-        domain_elements: init_domain_elements(&domain_area),
-        resolution_multiplier: SquareAlter,
-    };
+    let domain = domain::init(&domain_area, SquareAlter);
     let mut machine = Machine {
         area: &domain_area,
+        result_area: &domain_area,
         domain: &domain,
         calculation_config,
         app_config,
         result_config,
     };
 
-    machine.calculate(&nebula);
+    if let Err(e) = machine.calculate(&nebula) {
+        println!("Failed to save images: {}", e);
+    }
 
     println!("Finished.");
 }
@@ -70,3 +103,12 @@ fn test_math() {
     assert_eq!(m.re, 1.0);
     assert_eq!(m.im, 0.1);
 }
+
+#[test]
+fn test_math_dd() {
+    let nebula = Nebula {};
+    let mut m = MemDD::new(DoubleDouble::from(0.0), DoubleDouble::from(0.0));
+    nebula.math_dd(&mut m, DoubleDouble::from(1.0), DoubleDouble::from(0.1));
+    assert_eq!(m.re.to_f64(), 1.0);
+    assert_eq!(m.im.to_f64(), 0.1);
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier::SquareAlter;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_common::double_double::DoubleDouble;
+use rusty_fractals_core::camera;
+use rusty_fractals_core::camera::{Easing, Keyframe};
+use rusty_fractals_core::engine::Engine;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, MathDD, ResultConfig};
+use rusty_fractals_core::mem::Mem;
+use rusty_fractals_core::mem_dd::MemDD;
+use rusty_fractals_core::viewer;
+use rusty_fractals_domain::domain;
+use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
+use rusty_fractals_result::palettes::{palette_blue_to_white, palette_purple_to_white};
+
+// Flight from the whole Nebula to Nebula side
+struct NebulaFlight {}
+
+impl Math<Mem> for NebulaFlight {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+
+    // deep zoom
+    fn double_double(&self) -> Option<&dyn MathDD> {
+        Some(self)
+    }
+
+    fn perturbation(&self) -> bool {
+        true
+    }
+}
+
+impl MathDD for NebulaFlight {
+    fn math_dd(&self, m: &mut MemDD, origin_re: DoubleDouble, origin_im: DoubleDouble) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+}
+
+fn main() {
+    let name = "Nebula flight";
+
+    let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: Divergent,
+        iteration_min: 42,
+        iteration_max: 14800,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: true,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 600,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    // camera sets area of each frame
+    let area_cfg = area::AreaConfig {
+        width_re: 7.0,
+        center_re: 0.0,
+        center_im: 0.0,
+        width_x: 1280,
+        height_y: 720,
+        rotation: 0.0,
+    };
+    let result_config = ResultConfig {
+        palette: palette_blue_to_white(),
+        palette_zero: None,
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
+    };
+    let camera = camera::init(vec![
+        Keyframe { frame: 1, center_re: DoubleDouble::ZERO, center_im: DoubleDouble::ZERO, width_re: 7.0, rotation: 0.0, iteration_min: 42, iteration_max: 14800, palette: palette_blue_to_white(), easing: Easing::EaseIn },
+        Keyframe { frame: 300, center_re: DoubleDouble::from(-0.10675625916322415), center_im: DoubleDouble::from(-0.8914368889277283), width_re: 0.7, rotation: 0.5, iteration_min: 42, iteration_max: 14800, palette: palette_purple_to_white(), easing: Easing::EaseOut },
+        Keyframe { frame: 600, center_re: DoubleDouble::from(-0.10675625916322415), center_im: DoubleDouble::from(-0.8914368889277283), width_re: 0.007, rotation: 1.0, iteration_min: 42, iteration_max: 30000, palette: palette_purple_to_white(), easing: Easing::Linear },
+    ]);
+
+    println!("Fractal {}", name);
+
+    let nebula_flight = NebulaFlight {};
+    let mut area = area::init(area_cfg);
+    let mut result_area = area::init(area_cfg);
+    let mut domain = domain::init(&area, SquareAlter);
+    let viewer = viewer::init_window(name, result_area.width_x, result_area.height_y);
+    let mut engine = Engine {
+        area: &mut area,
+        result_area: &mut result_area,
+        domain: &mut domain,
+        calculation_config,
+        app_config,
+        result_config,
+        viewer,
+    };
+
+    if let Err(e) = engine.calculate_camera_path(&nebula_flight, &camera) {
+        println!("Failed to save images: {}", e);
+    }
+
+    println!("Finished.");
+}
+
+#[test]
+fn test_math() {
+    let nebula = NebulaFlight {};
+    let mut m = Mem { re: 0.0, im: 0.0 };
+    nebula.math(&mut m, 1.0, 0.1);
+    assert_eq!(m.re, 1.0);
+    assert_eq!(m.im, 0.1);
+}
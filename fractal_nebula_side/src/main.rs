@@ -1,68 +1,118 @@
-use rusty_fractals_result::palette::Palette;
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier::SquareAlter;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_common::double_double::DoubleDouble;
+use rusty_fractals_core::engine::Engine;
+use rusty_fractals_core::fractal::FractalType::Finebrot;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, CalculationConfig, Math, MathDD, ResultConfig};
 use rusty_fractals_core::mem::Mem;
-use rusty_fractals_core::fractal::{FractalConfig, FractalDefinition, Math};
+use rusty_fractals_core::mem_dd::MemDD;
+use rusty_fractals_core::viewer;
+use rusty_fractals_domain::domain;
 use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
 use rusty_fractals_result::palettes::palette_blue_to_white;
-use resolution_multiplier::ResolutionMultiplier;
-use resolution_multiplier::ResolutionMultiplier::SquareAlter;
-use log::{info};
 
-const NAME: &str = "Nebula side";
-const ITERATION_MAX: u32 = 14800;
-const ITERATION_MIN: u32 = 42;
-const AREA_SIZE: f64 = 7.0;
-const TARGET_RE: f64 = -0.10675625916322415;
-const TARGET_IM: f64 = -0.8914368889277283;
-const RESOLUTION_WIDTH: u32 = 1280;
-const RESOLUTION_HEIGHT: u32 = 720;
-const RESOLUTION_MULTIPLIER: ResolutionMultiplier = SquareAlter;
-const REPEAT: bool = true;
-const SAVE_IMAGES: bool = false;
-const PALETTE: Palette = palette_blue_to_white();
+struct NebulaSide {}
+
+impl Math<Mem> for NebulaSide {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+
+    // deep zoom
+    fn double_double(&self) -> Option<&dyn MathDD> {
+        Some(self)
+    }
 
-struct NebulaSide {
-    pub name: String,
+    fn perturbation(&self) -> bool {
+        true
+    }
 }
 
-impl Math for NebulaSide {
-    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+impl MathDD for NebulaSide {
+    fn math_dd(&self, m: &mut MemDD, origin_re: DoubleDouble, origin_im: DoubleDouble) {
         m.square();
         m.plus(origin_re, origin_im);
     }
 }
 
 fn main() {
-    info!("Started");
+    let name = "Nebula side";
 
-    let nebula = NebulaSide { name: NAME.to_string() };
-    let definition = FractalDefinition {
-        iteration_min: ITERATION_MIN,
-        iteration_max: ITERATION_MAX,
-        area_size: AREA_SIZE,
-        target_re: TARGET_RE,
-        target_im: TARGET_IM,
-        resolution_width: RESOLUTION_WIDTH,
-        resolution_height: RESOLUTION_HEIGHT,
-        resolution_multiplier: RESOLUTION_MULTIPLIER,
-        repeat: REPEAT,
-        save_images: SAVE_IMAGES,
-        palette: PALETTE
+    let calculation_config = CalculationConfig {
+        fractal_type: Finebrot,
+        path_selection: Divergent,
+        iteration_min: 42,
+        iteration_max: 14800,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: true,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1500,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    let area_cfg = area::AreaConfig {
+        width_re: 7.0,
+        center_re: -0.10675625916322415,
+        center_im: -0.8914368889277283,
+        width_x: 1280,
+        height_y: 720,
+        rotation: 0.0,
+    };
+    let result_config = ResultConfig {
+        palette: palette_blue_to_white(),
+        palette_zero: None,
+        bands: Vec::new(),
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
     };
 
-    info!("Fractal {}", nebula.name);
+    println!("Fractal {}", name);
 
-    let mut m = Mem { re: 0.0, im: 0.0 };
-    nebula.math(&mut m, 1.0, 0.1);
+    let nebula_side = NebulaSide {};
+    let mut area = area::init(area_cfg);
+    let mut result_area = area::init(area_cfg);
+    let mut domain = domain::init(&area, SquareAlter);
+    let viewer = viewer::init_window(name, result_area.width_x, result_area.height_y);
+    let mut engine = Engine {
+        area: &mut area,
+        result_area: &mut result_area,
+        domain: &mut domain,
+        calculation_config,
+        app_config,
+        result_config,
+        viewer,
+    };
 
-    info!("Finished.");
-}
+    if let Err(e) = engine.calculate(&nebula_side) {
+        println!("Failed to save images: {}", e);
+    }
 
+    println!("Finished.");
+}
 
 #[test]
 fn test_math() {
-    let nebula = NebulaSide { name: NAME.to_string() };
+    let nebula = NebulaSide {};
     let mut m = Mem { re: 0.0, im: 0.0 };
     nebula.math(&mut m, 1.0, 0.1);
     assert_eq!(m.re, 1.0);
     assert_eq!(m.im, 0.1);
 }
+
+#[test]
+fn test_math_dd() {
+    let nebula = NebulaSide {};
+    let mut m = MemDD::new(DoubleDouble::from(0.0), DoubleDouble::from(0.0));
+    nebula.math_dd(&mut m, DoubleDouble::from(1.0), DoubleDouble::from(0.1));
+    assert_eq!(m.re.to_f64(), 1.0);
+    assert_eq!(m.im.to_f64(), 0.1);
+}
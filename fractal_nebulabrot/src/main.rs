@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use resolution_multiplier::ResolutionMultiplier::SquareAlter;
+use rusty_fractals_common::area;
+use rusty_fractals_common::constants;
+use rusty_fractals_core::fractal::FractalType;
+use rusty_fractals_core::fractal::PathSelection::Divergent;
+use rusty_fractals_core::fractal::{AppConfig, Band, CalculationConfig, Math, ResultConfig};
+use rusty_fractals_core::machine::Machine;
+use rusty_fractals_core::mem::Mem;
+use rusty_fractals_domain::domain;
+use rusty_fractals_domain::resolution_multiplier;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::result_pixels::Splatting::Nearest;
+use rusty_fractals_result::palettes::{palette_black_to_blue, palette_black_to_green, palette_black_to_red, palette_black_to_white};
+
+struct Nebulabrot {}
+
+impl Math<Mem> for Nebulabrot {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+
+    // deep zoom
+    fn perturbation(&self) -> bool {
+        true
+    }
+}
+
+fn main() {
+    let name = "Nebulabrot";
+
+    let calculation_config = CalculationConfig {
+        fractal_type: FractalType::Nebulabrot,
+        path_selection: Divergent,
+        iteration_min: 42,
+        iteration_max: 14800,
+    };
+    let app_config = AppConfig {
+        name: name.to_string(),
+        repeat: false,
+        save_images: false,
+        output_dir: constants::PATH.to_string(),
+        frames: 1,
+        width_re_min: 0.0,
+        rotation: 0.0,
+    };
+    let area_cfg = area::AreaConfig {
+        width_re: 3.5,
+        center_re: -0.5,
+        center_im: 0.0,
+        width_x: 1280,
+        height_y: 720,
+        rotation: 0.0,
+    };
+    // short paths are blue, long paths are red
+    let result_config = ResultConfig {
+        palette: palette_black_to_white(),
+        palette_zero: None,
+        bands: vec![
+            Band { iteration_from: 42, iteration_to: 500, palette: palette_black_to_blue() },
+            Band { iteration_from: 500, iteration_to: 5000, palette: palette_black_to_green() },
+            Band { iteration_from: 5000, iteration_to: 14800, palette: palette_black_to_red() },
+        ],
+        coloring: Arc::new(Perfect {}),
+        splatting: Nearest,
+    };
+
+    println!("Fractal {}", name);
+
+    let nebulabrot = Nebulabrot {};
+    let domain_area = area::init(area_cfg);
+    let domain = domain::init(&domain_area, SquareAlter);
+    let mut machine = Machine {
+        area: &domain_area,
+        result_area: &domain_area,
+        domain: &domain,
+        calculation_config,
+        app_config,
+        result_config,
+    };
+
+    if let Err(e) = machine.calculate(&nebulabrot) {
+        println!("Failed to save images: {}", e);
+    }
+
+    println!("Finished.");
+}
+
+#[test]
+fn test_math() {
+    let nebulabrot = Nebulabrot {};
+    let mut m = Mem { re: 0.0, im: 0.0 };
+    nebulabrot.math(&mut m, 1.0, 0.1);
+    assert_eq!(m.re, 1.0);
+    assert_eq!(m.im, 0.1);
+}
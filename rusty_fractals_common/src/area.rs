@@ -1,14 +1,34 @@
-use crate::constants::ZOOM;
+use crate::constants::{PRECISION_MARGIN, ZOOM};
+use crate::double_double;
+use crate::double_double::DoubleDouble;
+use crate::target::Target;
 
+#[derive(Clone, Copy)]
 pub struct AreaConfig {
     pub width_re: f64,
     pub center_re: f64,
     pub center_im: f64,
     pub width_x: usize,
     pub height_y: usize,
+    // counterclockwise rotation of area around its center, in radians
+    pub rotation: f64,
 }
 
-const VANILLA_AREA_CONFIG: AreaConfig = AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 10, height_y: 10 };
+const VANILLA_AREA_CONFIG: AreaConfig = AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 10, height_y: 10, rotation: 0.0 };
+
+// Double: f64 coordinates are precise enough
+// DoubleDouble: area is zoomed in beyond f64 precision, coordinates are relative to double-double origin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Precision { Double, DoubleDouble }
+
+// Position of area before navigation, for undo
+// Center is absolute, origin of area may move before undo
+struct View {
+    center_re: DoubleDouble,
+    center_im: DoubleDouble,
+    width_re: f64,
+    rotation: f64,
+}
 
 pub struct Area {
     pub width_re: f64,
@@ -21,6 +41,16 @@ pub struct Area {
     pub numbers_im: Vec<f64>,
     pub center_re: f64,
     pub center_im: f64,
+    // All f64 coordinates of area and of calculation data are relative to origin
+    // Origin is [0, 0], until f64 isn't precise enough for zoomed in area
+    pub origin_re: DoubleDouble,
+    pub origin_im: DoubleDouble,
+    precision_warned: bool,
+    // numbers_re and numbers_im are not rotated, screen_to_domain rotates them around the center
+    rotation: f64,
+    rotation_sin: f64,
+    rotation_cos: f64,
+    history: Vec<View>,
     border_low_re: f64,
     border_low_im: f64,
     border_high_re: f64,
@@ -31,40 +61,240 @@ pub struct Area {
 
 impl Area {
     pub fn contains(&self, re: f64, im: f64) -> bool {
-        re > self.border_low_re
-            && re < self.border_high_re
-            && im > self.border_low_im
-            && im < self.border_high_im
+        if self.rotation == 0.0 {
+            return re > self.border_low_re
+                && re < self.border_high_re
+                && im > self.border_low_im
+                && im < self.border_high_im;
+        }
+        let (u, v) = self.unrotate(re - self.center_re, im - self.center_im);
+        u.abs() < self.width_half_re && v.abs() < self.height_half_im
+    }
+
+    pub fn plank(&self) -> f64 {
+        self.plank
+    }
+
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    pub fn screen_to_domain_re(&self, x: usize, y: usize) -> f64 {
+        if self.rotation == 0.0 {
+            return self.numbers_re[x];
+        }
+        let (u, v) = (self.numbers_re[x] - self.center_re, self.numbers_im[y] - self.center_im);
+        self.center_re + u * self.rotation_cos - v * self.rotation_sin
     }
 
-    pub fn screen_to_domain_re(&self, x: usize) -> f64 {
-        self.numbers_re[x]
+    pub fn screen_to_domain_im(&self, x: usize, y: usize) -> f64 {
+        if self.rotation == 0.0 {
+            return self.numbers_im[y];
+        }
+        let (u, v) = (self.numbers_re[x] - self.center_re, self.numbers_im[y] - self.center_im);
+        self.center_im + u * self.rotation_sin + v * self.rotation_cos
     }
 
-    pub fn screen_to_domain_im(&self, y: usize) -> f64 {
-        self.numbers_im[y]
+    // domain point of position in pixels, not rounded, inverse of domain_point_to_result_position
+    pub fn screen_position_to_domain(&self, px: f64, py: f64) -> (f64, f64) {
+        let u = (px - self.width_x as f64 / 2.0) * self.plank;
+        let v = (py - self.height_y as f64 / 2.0) * self.plank;
+        let (re, im) = self.rotate_offset(u, v);
+        (self.center_re + re, self.center_im + im)
+    }
+
+    // offset along the axes of screen rotated to the domain
+    pub fn rotate_offset(&self, u: f64, v: f64) -> (f64, f64) {
+        (u * self.rotation_cos - v * self.rotation_sin, u * self.rotation_sin + v * self.rotation_cos)
+    }
+
+    // offset from center rotated back to the axes of screen
+    fn unrotate(&self, re: f64, im: f64) -> (f64, f64) {
+        (re * self.rotation_cos + im * self.rotation_sin, im * self.rotation_cos - re * self.rotation_sin)
     }
 
     // check first, if can convert
     pub fn domain_point_to_result_pixel(&self, re: f64, im: f64) -> (usize, usize) {
-        let px = ((self.width_x as f64 * (re - self.center_re) / self.width_re) + self.width_half_re).round() as usize;
-        let py = ((self.height_y as f64 * (im - self.center_im) / self.height_im) + self.height_half_im).round() as usize;
+        let (px, py) = self.domain_point_to_result_position(re, im);
+        (px.round() as usize, py.round() as usize)
+    }
+
+    // position in pixels, not rounded, pixel centers are at whole numbers
+    pub fn domain_point_to_result_position(&self, re: f64, im: f64) -> (f64, f64) {
+        let (u, v) = self.unrotate(re - self.center_re, im - self.center_im);
+        let px = (self.width_x as f64 * u / self.width_re) + (self.width_x as f64 / 2.0);
+        let py = (self.height_y as f64 * v / self.height_im) + (self.height_y as f64 / 2.0);
         (px, py)
     }
 
+    pub fn precision(&self) -> Precision {
+        if self.origin_re == DoubleDouble::ZERO && self.origin_im == DoubleDouble::ZERO {
+            Precision::Double
+        } else {
+            Precision::DoubleDouble
+        }
+    }
+
+    // absolute coordinates of relative [re, im]
+    pub fn to_absolute(&self, re: f64, im: f64) -> (DoubleDouble, DoubleDouble) {
+        (self.origin_re + re, self.origin_im + im)
+    }
+
+    // relative coordinates of absolute [re, im]
+    pub fn to_relative(&self, re: DoubleDouble, im: DoubleDouble) -> (f64, f64) {
+        ((re - self.origin_re).to_f64(), (im - self.origin_im).to_f64())
+    }
+
+    // Switch to double-double precision, when pixels are too small for f64 coordinates of area center
+    // Returns [re, im] shift of all coordinates, previously calculated data has to be shifted by it too
+    pub fn check_precision(&mut self) -> Option<[f64; 2]> {
+        let center = self.center_re.abs().max(self.center_im.abs());
+        let shift = if self.plank < center * f64::EPSILON * PRECISION_MARGIN {
+            Some(self.rebase())
+        } else {
+            None
+        };
+        let origin = self.origin_re.abs().hi.max(self.origin_im.abs().hi);
+        if !self.precision_warned && self.plank < origin * double_double::EPSILON * PRECISION_MARGIN {
+            println!("Warning: double-double precision isn't enough for area width {}", self.width_re);
+            self.precision_warned = true;
+        }
+        shift
+    }
+
+    // Move origin to the center of area, returns [re, im] shift of coordinates
+    pub fn rebase(&mut self) -> [f64; 2] {
+        let shift = [self.center_re, self.center_im];
+        self.move_origin(self.center_re, self.center_im);
+        println!("Area origin moved to {:?}, {:?}, double-double precision", self.origin_re, self.origin_im);
+        shift
+    }
+
+    // other area lies inside of this one, borders may touch
+    pub fn covers(&self, other: &Area) -> bool {
+        // rounding of coordinates at the borders
+        let tolerance = self.plank * 1e-6;
+        let (w, h) = (other.width_x as f64, other.height_y as f64);
+        self.origin_re == other.origin_re
+            && self.origin_im == other.origin_im
+            && [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)].iter().all(|&(px, py)| {
+                let (re, im) = other.screen_position_to_domain(px, py);
+                let (u, v) = self.unrotate(re - self.center_re, im - self.center_im);
+                u.abs() <= self.width_half_re + tolerance && v.abs() <= self.height_half_im + tolerance
+            })
+    }
+
+    // Move origin by [re, im], area stays at the same place
+    // Areas calculated together have to share their origin
+    pub fn move_origin(&mut self, re: f64, im: f64) {
+        (self.origin_re, self.origin_im) = self.to_absolute(re, im);
+        self.center_re -= re;
+        self.center_im -= im;
+        self.initiate();
+    }
+
+    // one step of zoom video, not recorded in history
     pub fn zoom_in(&mut self) {
         println!("zoom_in()");
-        self.width_re = self.width_re * ZOOM;
-        self.height_im = self.height_im * ZOOM;
-        self.plank = self.width_re / self.width_x as f64;
-        // TODO initiate();
+        self.resize(self.width_re * ZOOM);
+        self.initiate();
+    }
+
+    pub fn zoom_out(&mut self) {
+        println!("zoom_out()");
+        self.resize(self.width_re / ZOOM);
+        self.initiate();
     }
 
-    // fn move_to_coordinates(&mut self) {
-    //     self.center_re = screenToDomainCreateRe(Target.getScreenFromCornerX());
-    //     self.center_im = screenToDomainCreateIm(Target.getScreenFromCornerY());
-    //     println!("Move to: {}, {}", self.center_re, self.center_im);
-    // }
+    // Navigation, each call can be reverted by undo()
+
+    // zoom by factor, domain point at [px, py] stays at the same position on the screen
+    // factor < 1 zooms in, factor > 1 zooms out
+    pub fn zoom_at(&mut self, px: f64, py: f64, factor: f64) {
+        self.save_view();
+        let (re, im) = self.screen_position_to_domain(px, py);
+        self.center_re = re + (self.center_re - re) * factor;
+        self.center_im = im + (self.center_im - im) * factor;
+        self.resize(self.width_re * factor);
+        self.initiate();
+    }
+
+    // move area by [dx, dy] pixels, content of the screen moves the opposite way
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.save_view();
+        let (re, im) = self.rotate_offset(dx * self.plank, dy * self.plank);
+        self.center_re += re;
+        self.center_im += im;
+        self.initiate();
+    }
+
+    // zoom to rectangle between two corners on the screen
+    // the whole rectangle stays visible, if its aspect ratio differs from the screen
+    pub fn zoom_to_rectangle(&mut self, x0: usize, y0: usize, x1: usize, y1: usize) {
+        self.save_view();
+        let (re, im) = self.screen_position_to_domain((x0 + x1) as f64 / 2.0, (y0 + y1) as f64 / 2.0);
+        let scale_x = (x0.abs_diff(x1) + 1) as f64 / self.width_x as f64;
+        let scale_y = (y0.abs_diff(y1) + 1) as f64 / self.height_y as f64;
+        self.center_re = re;
+        self.center_im = im;
+        self.resize(self.width_re * scale_x.max(scale_y));
+        self.initiate();
+    }
+
+    // move center to domain coordinates
+    pub fn move_to(&mut self, re: f64, im: f64) {
+        self.save_view();
+        self.center_re = re;
+        self.center_im = im;
+        println!("Move to: {}, {}", self.center_re, self.center_im);
+        self.initiate();
+    }
+
+    // return to the area before last navigation, false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(view) => {
+                (self.center_re, self.center_im) = self.to_relative(view.center_re, view.center_im);
+                self.rotation = view.rotation;
+                self.resize(view.width_re);
+                self.initiate();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn save_view(&mut self) {
+        let (center_re, center_im) = self.to_absolute(self.center_re, self.center_im);
+        self.history.push(View { center_re, center_im, width_re: self.width_re, rotation: self.rotation });
+    }
+
+    // height keeps aspect ratio of the screen
+    fn resize(&mut self, width_re: f64) {
+        self.width_re = width_re;
+        self.height_im = width_re * (self.height_y as f64 / self.width_x as f64);
+    }
+
+    // set the whole view at once, for frames of camera path, not recorded in history
+    pub fn set_view(&mut self, center_re: f64, center_im: f64, width_re: f64, rotation: f64) {
+        self.center_re = center_re;
+        self.center_im = center_im;
+        self.rotation = rotation;
+        self.resize(width_re);
+        self.initiate();
+    }
+
+    // rotate area around its center by angle, in radians
+    pub fn rotate(&mut self, angle: f64) {
+        self.rotation += angle;
+        self.initiate();
+    }
+
+    // move center to where the user clicked
+    pub fn move_to_coordinates(&mut self, target: &Target) {
+        let (x, y) = (target.screen_from_corner_x(), target.screen_from_corner_y());
+        self.move_to(self.screen_to_domain_re(x, y), self.screen_to_domain_im(x, y));
+    }
 
     /**
      * move to zoom target
@@ -73,58 +303,73 @@ impl Area {
         println!("move_to_initial_coordinates()");
         self.center_re = init_target_re;
         self.center_im = init_target_im;
+        self.initiate();
+    }
+
+    // recalculate all the values derived from center and size
+    fn initiate(&mut self) {
+        self.plank = self.width_re / self.width_x as f64;
+        (self.rotation_sin, self.rotation_cos) = self.rotation.sin_cos();
+        self.width_half_re = self.width_re / 2.0;
+        self.height_half_im = self.height_im / 2.0;
+        self.border_low_re = self.center_re - self.width_half_re;
+        self.border_high_re = self.center_re + self.width_half_re;
+        self.border_low_im = self.center_im - self.height_half_im;
+        self.border_high_im = self.center_im + self.height_half_im;
+
+        /* Generate domain elements */
+        self.numbers_re.clear();
+        self.numbers_im.clear();
+        for x in 0..self.width_x {
+            self.numbers_re.push(self.border_low_re + (self.plank * x as f64));
+        }
+        for y in 0..self.height_y {
+            self.numbers_im.push(self.border_low_im + (self.plank * y as f64));
+        }
     }
 }
 
 pub fn init(config: AreaConfig) -> Area {
     println!("init()");
     let width_re = config.width_re;
-    let center_re = config.center_re;
-    let center_im = config.center_im;
     let width_x = config.width_x;
     let height_y = config.height_y;
+    let height_im = width_re * (height_y as f64 / width_x as f64);
 
-    let plank = width_re / width_x as f64;
-    let height_im = width_re * (width_x as f64 / height_y as f64);
-    let width_half_re = width_re / 2.0;
-    let height_half_im = height_im / 2.0;
-    let border_low_re = center_re - (width_half_re);
-    let border_high_re = center_re + (width_half_re);
-    let border_low_im = center_im - (height_half_im);
-    let border_high_im = center_im + (height_half_im);
-
-    println!("border_low_re  {}", border_low_re);
-    println!("border_high_re {}", border_high_re);
-    println!("border_low_im  {}", border_low_im);
-    println!("border_high_im {}", border_high_im);
-
-    /* Generate domain elements */
-    let mut numbers_re: Vec<f64> = Vec::new();
-    let mut numbers_im: Vec<f64> = Vec::new();
-    for x in 0..width_x {
-        numbers_re.push(border_low_re + (plank * x as f64));
-    }
-    for y in 0..height_y {
-        numbers_im.push(border_low_im + (plank * y as f64));
-    }
-
-    Area {
+    let mut area = Area {
         width_re,
         height_im,
-        width_half_re,
-        height_half_im,
+        width_half_re: 0.0,
+        height_half_im: 0.0,
         width_x,
         height_y,
-        numbers_re,
-        numbers_im,
-        center_re,
-        center_im,
-        border_low_re,
-        border_low_im,
-        border_high_re,
-        border_high_im,
-        plank,
-    }
+        numbers_re: Vec::new(),
+        numbers_im: Vec::new(),
+        center_re: config.center_re,
+        center_im: config.center_im,
+        origin_re: DoubleDouble::ZERO,
+        origin_im: DoubleDouble::ZERO,
+        precision_warned: false,
+        rotation: config.rotation,
+        rotation_sin: 0.0,
+        rotation_cos: 1.0,
+        history: Vec::new(),
+        border_low_re: 0.0,
+        border_low_im: 0.0,
+        border_high_re: 0.0,
+        border_high_im: 0.0,
+        plank: 0.0,
+    };
+    area.initiate();
+    // nothing is calculated yet, shift can be ignored
+    area.check_precision();
+
+    println!("border_low_re  {}", area.border_low_re);
+    println!("border_high_re {}", area.border_high_re);
+    println!("border_low_im  {}", area.border_low_im);
+    println!("border_high_im {}", area.border_high_im);
+
+    area
 }
 
 #[test]
@@ -148,13 +393,244 @@ fn test_contains() {
 #[test]
 fn test_screen_to_domain_re() {
     let area = init(VANILLA_AREA_CONFIG);
-    let r = area.screen_to_domain_re(500);
-    assert_eq!(r, 0.125);
+    // 10 pixels, 0.1 each, from -0.5
+    let r = area.screen_to_domain_re(7, 0);
+    assert!((r - 0.2).abs() < 1e-12);
 }
 
 #[test]
 fn test_screen_to_domain_im() {
     let area = init(VANILLA_AREA_CONFIG);
-    let i = area.screen_to_domain_im(20);
-    assert_eq!(i, -0.475);
+    let i = area.screen_to_domain_im(0, 1);
+    assert!((i - -0.4).abs() < 1e-12);
+}
+
+#[test]
+fn test_zoom_in() {
+    let mut area = init(VANILLA_AREA_CONFIG);
+    area.zoom_in();
+    assert_eq!(area.border_low_re, -0.49);
+    assert_eq!(area.border_high_re, 0.49);
+    assert_eq!(area.numbers_re[0], -0.49);
+    assert!(!area.contains(0.495, 0.0));
+}
+
+#[test]
+fn test_domain_point_to_result_pixel() {
+    let area = init(AreaConfig { width_re: 2.0, center_re: -0.5, center_im: 0.0, width_x: 20, height_y: 10, rotation: 0.0 });
+    assert_eq!(area.height_im, 1.0);
+    assert_eq!(area.domain_point_to_result_pixel(-0.5, 0.0), (10, 5));
+    assert_eq!(area.domain_point_to_result_pixel(area.screen_to_domain_re(3, 7), area.screen_to_domain_im(3, 7)), (3, 7));
+}
+
+#[test]
+fn test_domain_point_to_result_position() {
+    let area = init(AreaConfig { width_re: 2.0, center_re: -0.5, center_im: 0.0, width_x: 20, height_y: 10, rotation: 0.0 });
+    let (px, py) = area.domain_point_to_result_position(area.screen_to_domain_re(3, 7) + area.plank() / 4.0, area.screen_to_domain_im(3, 7));
+    assert!((px - 3.25).abs() < 1e-9);
+    assert!((py - 7.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_move_to_coordinates() {
+    use crate::target;
+
+    let mut area = init(AreaConfig { width_re: 2.0, center_re: -0.5, center_im: 0.0, width_x: 20, height_y: 10, rotation: 0.0 });
+    let mut target = target::init(20, 10);
+    target.update(3, 7);
+    let (re, im) = (area.screen_to_domain_re(3, 7), area.screen_to_domain_im(3, 7));
+
+    area.move_to_coordinates(&target);
+
+    assert_eq!(area.center_re, re);
+    assert_eq!(area.center_im, im);
+    assert_eq!(area.domain_point_to_result_pixel(re, im), (10, 5));
+}
+
+#[test]
+fn test_check_precision() {
+    let mut area = init(AreaConfig { width_re: 1e-9, center_re: -0.75, center_im: 0.1, width_x: 100, height_y: 100, rotation: 0.0 });
+    assert_eq!(area.precision(), Precision::Double);
+    assert_eq!(area.check_precision(), None);
+
+    let mut shift = None;
+    while shift.is_none() {
+        area.zoom_in();
+        shift = area.check_precision();
+    }
+    assert_eq!(shift, Some([-0.75, 0.1]));
+    assert_eq!(area.precision(), Precision::DoubleDouble);
+    assert_eq!(area.center_re, 0.0);
+    assert_eq!(area.origin_re.to_f64(), -0.75);
+    // f64 can't tell neighbour pixels apart at -0.75, relative coordinates can
+    let (re_0, _) = area.to_absolute(area.screen_to_domain_re(0, 0), 0.0);
+    let (re_1, _) = area.to_absolute(area.screen_to_domain_re(1, 0), 0.0);
+    assert!(((re_1 - re_0).to_f64() - area.plank()).abs() < area.plank() * 1e-6);
+    assert_eq!(area.to_relative(re_1, area.origin_im), (area.screen_to_domain_re(1, 0), 0.0));
+}
+
+#[test]
+fn test_init_double_double() {
+    let area = init(AreaConfig { width_re: 1e-14, center_re: 0.3, center_im: 0.0, width_x: 100, height_y: 100, rotation: 0.0 });
+    assert_eq!(area.precision(), Precision::DoubleDouble);
+    assert_eq!(area.origin_re.to_f64(), 0.3);
+    assert!(area.contains(area.plank(), 0.0));
+    assert!(!area.contains(0.3, 0.0));
+}
+
+#[test]
+fn test_rotation_round_trip() {
+    let area = init(AreaConfig { width_re: 2.0, center_re: -0.5, center_im: 0.25, width_x: 20, height_y: 10, rotation: 0.7 });
+    for (x, y) in [(1, 1), (3, 7), (19, 9), (10, 5)] {
+        let (re, im) = (area.screen_to_domain_re(x, y), area.screen_to_domain_im(x, y));
+        assert!(area.contains(re, im));
+        assert_eq!(area.domain_point_to_result_pixel(re, im), (x, y));
+    }
+}
+
+#[test]
+fn test_rotation_quarter_turn() {
+    use std::f64::consts::FRAC_PI_2;
+
+    let area = init(AreaConfig { width_re: 2.0, center_re: 0.0, center_im: 0.0, width_x: 20, height_y: 10, rotation: FRAC_PI_2 });
+    // screen x axis goes along im axis
+    assert!((area.screen_to_domain_re(15, 5) - 0.0).abs() < 1e-12);
+    assert!((area.screen_to_domain_im(15, 5) - 0.5).abs() < 1e-12);
+    // area is 2.0 wide and 1.0 high on the screen, rotated it is 1.0 wide and 2.0 high
+    assert!(area.contains(0.4, 0.9));
+    assert!(!area.contains(0.9, 0.4));
+}
+
+#[test]
+fn test_rotate() {
+    let mut area = init(VANILLA_AREA_CONFIG);
+    area.rotate(0.25);
+    area.rotate(0.25);
+    assert_eq!(area.rotation(), 0.5);
+    let (px, py) = area.domain_point_to_result_position(0.4 * 0.5_f64.cos(), 0.4 * 0.5_f64.sin());
+    assert!((px - 9.0).abs() < 1e-9);
+    assert!((py - 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_zoom_at() {
+    let mut area = init(AreaConfig { width_re: 2.0, center_re: -0.5, center_im: 0.0, width_x: 20, height_y: 10, rotation: 0.3 });
+    let (re, im) = area.screen_position_to_domain(3.0, 7.0);
+    area.zoom_at(3.0, 7.0, 0.5);
+    assert_eq!(area.width_re, 1.0);
+    assert_eq!(area.height_im, 0.5);
+    assert!((area.plank() - 0.05).abs() < 1e-12);
+    let (px, py) = area.domain_point_to_result_position(re, im);
+    assert!((px - 3.0).abs() < 1e-9);
+    assert!((py - 7.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_zoom_out() {
+    let mut area = init(VANILLA_AREA_CONFIG);
+    area.zoom_in();
+    area.zoom_out();
+    assert!((area.width_re - 1.0).abs() < 1e-12);
+    assert!((area.height_im - 1.0).abs() < 1e-12);
+    assert!((area.numbers_re[0] + 0.5).abs() < 1e-12);
+    assert!(area.contains(0.495, 0.0));
+}
+
+#[test]
+fn test_pan() {
+    use std::f64::consts::FRAC_PI_2;
+
+    let mut area = init(VANILLA_AREA_CONFIG);
+    area.pan(2.0, -1.0);
+    assert!((area.center_re - 0.2).abs() < 1e-12);
+    assert!((area.center_im + 0.1).abs() < 1e-12);
+    assert!((area.screen_to_domain_re(0, 0) + 0.3).abs() < 1e-12);
+    assert!(!area.contains(-0.35, 0.0));
+
+    // screen x axis goes along im axis
+    let mut area = init(AreaConfig { rotation: FRAC_PI_2, ..VANILLA_AREA_CONFIG });
+    area.pan(2.0, 0.0);
+    assert!(area.center_re.abs() < 1e-12);
+    assert!((area.center_im - 0.2).abs() < 1e-12);
+}
+
+#[test]
+fn test_zoom_to_rectangle() {
+    let mut area = init(AreaConfig { width_re: 2.0, center_re: -0.5, center_im: 0.0, width_x: 20, height_y: 10, rotation: 0.0 });
+    let corner_a = (area.screen_to_domain_re(4, 2), area.screen_to_domain_im(4, 2));
+    let corner_b = (area.screen_to_domain_re(7, 8), area.screen_to_domain_im(7, 8));
+    area.zoom_to_rectangle(7, 8, 4, 2);
+    // rectangle is 4 x 7 pixels, height decides
+    assert!((area.height_im - 0.7).abs() < 1e-12);
+    assert!((area.width_re - 1.4).abs() < 1e-12);
+    assert!((area.center_re - (corner_a.0 + corner_b.0) / 2.0).abs() < 1e-12);
+    assert!((area.center_im - (corner_a.1 + corner_b.1) / 2.0).abs() < 1e-12);
+    assert!(area.contains(corner_a.0, corner_a.1));
+    assert!(area.contains(corner_b.0, corner_b.1));
+}
+
+#[test]
+fn test_undo() {
+    let mut area = init(AreaConfig { width_re: 2.0, center_re: -0.5, center_im: 0.0, width_x: 20, height_y: 10, rotation: 0.0 });
+    area.move_to(0.25, 0.125);
+    area.zoom_at(3.0, 7.0, 0.5);
+    area.pan(1.0, 1.0);
+
+    assert!(area.undo());
+    assert!(area.undo());
+    assert_eq!((area.center_re, area.center_im, area.width_re), (0.25, 0.125, 2.0));
+    assert!(area.undo());
+    assert_eq!((area.center_re, area.center_im, area.height_im), (-0.5, 0.0, 1.0));
+    assert_eq!(area.numbers_re[0], -1.5);
+    assert!(!area.undo());
+}
+
+#[test]
+fn test_undo_after_rebase() {
+    let mut area = init(AreaConfig { width_re: 1e-9, center_re: -0.75, center_im: 0.1, width_x: 100, height_y: 100, rotation: 0.0 });
+    area.zoom_at(50.0, 50.0, 1e-6);
+    assert!(area.check_precision().is_some());
+    assert!(area.undo());
+    // center is relative to the new origin
+    let (re, im) = area.to_absolute(area.center_re, area.center_im);
+    assert_eq!((re.to_f64(), im.to_f64()), (-0.75, 0.1));
+    assert_eq!(area.width_re, 1e-9);
+}
+
+#[test]
+fn test_covers() {
+    let area = init(VANILLA_AREA_CONFIG);
+    assert!(area.covers(&init(VANILLA_AREA_CONFIG)));
+    assert!(area.covers(&init(AreaConfig { width_re: 0.5, center_re: 0.2, ..VANILLA_AREA_CONFIG })));
+    assert!(!area.covers(&init(AreaConfig { width_re: 0.5, center_re: 0.3, ..VANILLA_AREA_CONFIG })));
+    // corners of rotated area stick out
+    assert!(!area.covers(&init(AreaConfig { rotation: 0.5, ..VANILLA_AREA_CONFIG })));
+    // the same area, relative to other origin
+    let mut rebased = init(VANILLA_AREA_CONFIG);
+    rebased.origin_re = DoubleDouble::from(1.0);
+    assert!(!area.covers(&rebased));
+}
+
+#[test]
+fn test_move_origin() {
+    let mut area = init(AreaConfig { center_re: -0.75, center_im: 0.25, ..VANILLA_AREA_CONFIG });
+    let (center_re, center_im) = area.to_absolute(area.center_re, area.center_im);
+    area.move_origin(-0.5, 0.5);
+    assert_eq!(area.center_re, -0.25);
+    assert_eq!(area.center_im, -0.25);
+    assert_eq!(area.to_absolute(area.center_re, area.center_im), (center_re, center_im));
+}
+
+#[test]
+fn test_set_view() {
+    let mut area = init(VANILLA_AREA_CONFIG);
+    area.zoom_at(2.0, 2.0, 0.5);
+    area.set_view(0.5, -0.25, 2.0, 0.1);
+    assert_eq!((area.center_re, area.center_im, area.width_re, area.height_im), (0.5, -0.25, 2.0, 2.0));
+    assert_eq!(area.rotation(), 0.1);
+    assert!((area.plank() - 0.2).abs() < 1e-12);
+    assert_eq!(area.domain_point_to_result_pixel(0.5, -0.25), (5, 5));
+    // only navigation is in history
+    assert!(area.undo());
+    assert_eq!(area.width_re, 1.0);
 }
@@ -7,17 +7,31 @@ pub const NEIGHBOURS: u8 = 3;
 pub const PATH: &str = "/Fractals/";
 
 // to remove noise, tread pixels with this value or less as 0
-pub const COLORING_THRESHOLD: u32 = 3;
+pub const COLORING_THRESHOLD: f64 = 3.0;
+
+// Domain is calculated in CHUNKS x CHUNKS chunks, the last chunks in each direction take also the remainder pixels
+pub const CHUNKS: u32 = 20;
 
 // Delete shorter paths then this
 pub const MINIMUM_PATH_LENGTH: u32 = 4;
 
+// Non-divergent paths are up to iteration_max points long
+// Stop keeping calculation paths for next frames of zoom, when all kept paths have this many points, 16 bytes each
+pub const PATHS_POINTS_MAX: usize = 50_000_000;
+
 // 4 is quadrance from (0, 0)
 // If intermediate calculation result [re,im] spirals beyond this boundary. Calculation stops as divergent.
 pub const CALCULATION_BOUNDARY: u32 = 4;
 
+// Viewer shows unfinished frame this often, in milliseconds
+pub const REFRESH_MILLIS: u64 = 500;
+
 // 0.98 is a good choice for 25fps and moderate speed
 pub const ZOOM: f64 = 0.98;
 
 // take result data snapshot for comparison at well colored frame
 pub const TAKE_MEASURES_AT_FRAME: u32 = 20;
+
+// Pixel size must be this many times larger than the smallest difference of area coordinates
+// Otherwise area switches to double-double precision
+pub const PRECISION_MARGIN: f64 = 10_000.0;
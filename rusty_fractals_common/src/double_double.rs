@@ -0,0 +1,126 @@
+// Double-double number, unevaluated sum of two f64, hi + lo, |lo| <= ulp(hi) / 2
+// About 32 significant digits, for coordinates of deep zoom where f64 isn't precise enough
+// https://www.davidhbailey.com/dhbpapers/qd.pdf
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+// relative precision of double-double, 2^-104
+pub const EPSILON: f64 = f64::EPSILON * f64::EPSILON / 4.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    pub const ZERO: DoubleDouble = DoubleDouble { hi: 0.0, lo: 0.0 };
+
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    pub fn abs(self) -> DoubleDouble {
+        if self.hi < 0.0 { -self } else { self }
+    }
+
+    pub fn square(self) -> DoubleDouble {
+        self * self
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        DoubleDouble { hi: value, lo: 0.0 }
+    }
+}
+
+// exact sum, s + e = a + b
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+// exact sum, if |a| >= |b|
+fn quick_two_sum(a: f64, b: f64) -> DoubleDouble {
+    let s = a + b;
+    DoubleDouble { hi: s, lo: b - (s - a) }
+}
+
+// exact product, p + e = a * b
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+impl Add for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn add(self, b: DoubleDouble) -> DoubleDouble {
+        let (s, e) = two_sum(self.hi, b.hi);
+        let (t, f) = two_sum(self.lo, b.lo);
+        let r = quick_two_sum(s, e + t);
+        quick_two_sum(r.hi, r.lo + f)
+    }
+}
+
+impl Add<f64> for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn add(self, b: f64) -> DoubleDouble {
+        let (s, e) = two_sum(self.hi, b);
+        quick_two_sum(s, e + self.lo)
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn neg(self) -> DoubleDouble {
+        DoubleDouble { hi: -self.hi, lo: -self.lo }
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn sub(self, b: DoubleDouble) -> DoubleDouble {
+        self + -b
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, b: DoubleDouble) -> DoubleDouble {
+        let (p, e) = two_prod(self.hi, b.hi);
+        quick_two_sum(p, e + (self.hi * b.lo + self.lo * b.hi))
+    }
+}
+
+impl Mul<f64> for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, b: f64) -> DoubleDouble {
+        let (p, e) = two_prod(self.hi, b);
+        quick_two_sum(p, e + self.lo * b)
+    }
+}
+
+#[test]
+fn test_add() {
+    let one = DoubleDouble::from(1.0);
+    let tiny = DoubleDouble::from(1e-20);
+    // f64 would lose the tiny part
+    assert_eq!((one + tiny) - one, tiny);
+    assert_eq!((one + 1e-20 - one).to_f64(), 1e-20);
+}
+
+#[test]
+fn test_mul() {
+    let a = DoubleDouble::from(1.0) + 1e-20;
+    let square = a.square() - DoubleDouble::from(1.0);
+    assert!((square.to_f64() - 2e-20).abs() < 1e-35);
+    assert_eq!((DoubleDouble::from(3.0) * 0.5).to_f64(), 1.5);
+}
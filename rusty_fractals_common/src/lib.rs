@@ -1,3 +1,4 @@
 pub mod area;
 pub mod target;
 pub mod constants;
+pub mod double_double;
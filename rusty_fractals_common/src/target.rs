@@ -3,44 +3,60 @@
 pub struct Target {
     pub width: usize,
     pub height: usize,
-    pub scr_x: usize,
-    pub scr_y: usize,
+    scr_corner_x: usize,
+    scr_corner_y: usize,
 }
 
 impl Target {
     pub fn update(&mut self, mouse_position_x: usize, mouse_position_y: usize) {
-        let scr_corner_re = mouse_position_x;
-        let scr_corner_im = mouse_position_y;
-        self.scr_x = (scr_corner_re - (self.width / 2)) as usize;
-        self.scr_y = (scr_corner_im - (self.height / 2)) as usize;
+        self.scr_corner_x = mouse_position_x.min(self.width - 1);
+        self.scr_corner_y = mouse_position_y.min(self.height - 1);
     }
 
-    pub fn screen_from_center_x(&self) -> usize {
-        self.scr_x
+    // negative left from the center
+    pub fn screen_from_center_x(&self) -> i64 {
+        self.scr_corner_x as i64 - (self.width / 2) as i64
     }
 
-    pub fn screen_from_center_y(&self) -> usize {
-        self.scr_y
+    // negative above the center
+    pub fn screen_from_center_y(&self) -> i64 {
+        self.scr_corner_y as i64 - (self.height / 2) as i64
     }
 
     pub fn screen_from_corner_x(&self) -> usize {
-        // TODO self.scr_corner_re
-        0
+        self.scr_corner_x
     }
 
     pub fn screen_from_corner_y(&self) -> usize {
-        // TODO self.scr_corner_im
-        0
+        self.scr_corner_y
     }
 }
 
-/*
-fn init(width_x: usize, height_y: usize) -> Target {
+// target is at the center of the screen at the beginning
+pub fn init(width_x: usize, height_y: usize) -> Target {
     Target {
         width: width_x,
         height: height_y,
-        scr_x: (width_x as f64 / 2.0) as usize,
-        scr_y: (height_y as f64 / 2.0) as usize,
+        scr_corner_x: width_x / 2,
+        scr_corner_y: height_y / 2,
     }
 }
-*/
\ No newline at end of file
+
+#[test]
+fn test_update() {
+    let mut target = init(100, 50);
+    assert_eq!(target.screen_from_center_x(), 0);
+    assert_eq!(target.screen_from_center_y(), 0);
+
+    target.update(10, 40);
+
+    assert_eq!(target.screen_from_corner_x(), 10);
+    assert_eq!(target.screen_from_corner_y(), 40);
+    assert_eq!(target.screen_from_center_x(), -40);
+    assert_eq!(target.screen_from_center_y(), 15);
+
+    target.update(120, 60);
+
+    assert_eq!(target.screen_from_corner_x(), 99);
+    assert_eq!(target.screen_from_corner_y(), 49);
+}
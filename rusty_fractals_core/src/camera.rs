@@ -0,0 +1,192 @@
+// Camera path of zoom animation
+// Keyframes set view of area, iteration limits and palette at given frames, frames between them are interpolated
+// Width changes exponentially, zoom speed is constant between two keyframes
+// Center moves together with the width, frames between two keyframes zoom into a single point, which stays at the same place of the screen
+
+use rusty_fractals_common::double_double::DoubleDouble;
+use rusty_fractals_result::color_space;
+use rusty_fractals_result::color_space::Interpolation;
+use rusty_fractals_result::palette::Palette;
+
+// How is the transition from one keyframe to the next one spent
+// Linear: constant speed
+// EaseIn: starts slowly
+// EaseOut: stops slowly
+// EaseInOut: starts and stops slowly
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing { Linear, EaseIn, EaseOut, EaseInOut }
+
+impl Easing {
+    // eased progress of transition, t goes from 0 to 1
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Keyframe {
+    // frames are numbered from 1
+    pub frame: u32,
+    // absolute coordinates, precise enough for deep zoom
+    pub center_re: DoubleDouble,
+    pub center_im: DoubleDouble,
+    pub width_re: f64,
+    // radians
+    pub rotation: f64,
+    pub iteration_min: u32,
+    pub iteration_max: u32,
+    pub palette: Palette,
+    // transition to the next keyframe
+    pub easing: Easing,
+}
+
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    // the last frame of animation
+    pub fn frames(&self) -> u32 {
+        self.keyframes[self.keyframes.len() - 1].frame
+    }
+
+    // View of any frame, frames out of the path keep the first or the last keyframe
+    pub fn frame(&self, frame: u32) -> Keyframe {
+        let next = self.keyframes.iter().position(|k| k.frame > frame);
+        let (from, to) = match next {
+            Some(0) => return Keyframe { frame, ..self.keyframes[0].clone() },
+            None => return Keyframe { frame, ..self.keyframes[self.keyframes.len() - 1].clone() },
+            Some(i) => (&self.keyframes[i - 1], &self.keyframes[i]),
+        };
+        let t = from.easing.apply((frame - from.frame) as f64 / (to.frame - from.frame) as f64);
+
+        let width_re = from.width_re * (to.width_re / from.width_re).powf(t);
+        // how far is the zoom, the center gets to the next one when the width does
+        let s = if from.width_re == to.width_re { t } else { (from.width_re - width_re) / (from.width_re - to.width_re) };
+        let lerp = |a: f64, b: f64, f: f64| a + (b - a) * f;
+        let lerp_dd = |a: DoubleDouble, b: DoubleDouble, f: f64| a + (b - a) * f;
+        Keyframe {
+            frame,
+            center_re: lerp_dd(from.center_re, to.center_re, s),
+            center_im: lerp_dd(from.center_im, to.center_im, s),
+            width_re,
+            rotation: lerp(from.rotation, to.rotation, t),
+            iteration_min: lerp(from.iteration_min as f64, to.iteration_min as f64, t).round() as u32,
+            iteration_max: lerp(from.iteration_max as f64, to.iteration_max as f64, t).round() as u32,
+            palette: blend(&from.palette, &to.palette, t),
+            easing: from.easing,
+        }
+    }
+}
+
+// colors of both spectra at the same relative positions are mixed
+fn blend(from: &Palette, to: &Palette, f: f64) -> Palette {
+    if f <= 0.0 {
+        return from.clone();
+    }
+    if f >= 1.0 {
+        return to.clone();
+    }
+    let (n, m) = (from.spectrum.len(), to.spectrum.len());
+    let spectrum = (0..n)
+        .map(|i| {
+            let j = if n > 1 { i * (m - 1) / (n - 1) } else { 0 };
+            color_space::mix(from.spectrum[i], to.spectrum[j], f, Interpolation::OkLab)
+        })
+        .collect();
+    Palette { spectrum }
+}
+
+// keyframes have to be ordered by their frames
+pub fn init(keyframes: Vec<Keyframe>) -> CameraPath {
+    assert!(!keyframes.is_empty(), "camera path without keyframes");
+    assert!(keyframes.windows(2).all(|w| w[0].frame < w[1].frame), "keyframes aren't ordered by frames");
+    CameraPath { keyframes }
+}
+
+#[cfg(test)]
+fn test_keyframe(frame: u32, center_re: f64, width_re: f64) -> Keyframe {
+    use rusty_fractals_result::palettes::palette_black_to_white;
+
+    Keyframe { frame, center_re: DoubleDouble::from(center_re), center_im: DoubleDouble::ZERO, width_re, rotation: 0.0, iteration_min: 10, iteration_max: 1000, palette: palette_black_to_white(), easing: Easing::Linear }
+}
+
+#[test]
+fn test_easing() {
+    for easing in [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut] {
+        assert_eq!(easing.apply(0.0), 0.0);
+        assert_eq!(easing.apply(1.0), 1.0);
+    }
+    assert!(Easing::EaseIn.apply(0.25) < 0.25);
+    assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+}
+
+#[test]
+fn test_frame_keyframes() {
+    let camera = init(vec![test_keyframe(1, 0.0, 4.0), test_keyframe(11, -1.0, 0.04)]);
+    assert_eq!(camera.frames(), 11);
+    assert_eq!(camera.frame(1).width_re, 4.0);
+    assert_eq!(camera.frame(11).center_re, DoubleDouble::from(-1.0));
+    // out of the path
+    assert_eq!(camera.frame(0).width_re, 4.0);
+    assert_eq!(camera.frame(20).width_re, 0.04);
+    assert_eq!(camera.frame(20).frame, 20);
+}
+
+#[test]
+fn test_frame_zoom() {
+    let mut from = test_keyframe(1, 0.0, 4.0);
+    from.rotation = 1.0;
+    from.iteration_max = 100;
+    let camera = init(vec![from, test_keyframe(3, -1.0, 0.04)]);
+
+    let middle = camera.frame(2);
+    // constant zoom speed
+    assert!((middle.width_re - 0.4).abs() < 1e-12);
+    assert_eq!(middle.rotation, 0.5);
+    assert_eq!(middle.iteration_max, 550);
+    // fixed point of the zoom stays at the same place of the screen
+    let fixed = -1.0 / 0.99;
+    let screen_position = |k: &Keyframe| (fixed - k.center_re.to_f64()) / k.width_re;
+    assert!((screen_position(&camera.frame(1)) - screen_position(&middle)).abs() < 1e-9);
+    assert!((screen_position(&camera.frame(3)) - screen_position(&middle)).abs() < 1e-9);
+}
+
+#[test]
+fn test_frame_deep_center() {
+    let deep = DoubleDouble { hi: -0.75, lo: 1e-20 };
+    let camera = init(vec![test_keyframe(1, -0.75, 1e-15), Keyframe { center_re: deep, ..test_keyframe(3, 0.0, 1e-15) }]);
+    // f64 would lose the difference of keyframes
+    let middle = camera.frame(2).center_re - DoubleDouble::from(-0.75);
+    assert!((middle.to_f64() - 0.5e-20).abs() < 1e-30);
+    assert_eq!(camera.frame(3).center_re, deep);
+}
+
+#[test]
+fn test_frame_palette() {
+    use image::Rgb;
+
+    let black = Palette { spectrum: vec![Rgb([0, 0, 0]); 4] };
+    let white = Palette { spectrum: vec![Rgb([255, 255, 255]); 8] };
+    let camera = init(vec![
+        Keyframe { palette: black, ..test_keyframe(1, 0.0, 1.0) },
+        Keyframe { palette: white, ..test_keyframe(5, 0.0, 1.0) },
+    ]);
+    assert_eq!(camera.frame(1).palette.spectrum[0], Rgb([0, 0, 0]));
+    assert_eq!(camera.frame(5).palette.spectrum.len(), 8);
+    let gray = camera.frame(3).palette.spectrum[3];
+    assert!(gray[0] > 0 && gray[0] < 255);
+    assert_eq!(camera.frame(3).palette.spectrum.len(), 4);
+}
+
+#[test]
+#[should_panic]
+fn test_init_unordered() {
+    init(vec![test_keyframe(5, 0.0, 1.0), test_keyframe(5, 0.0, 1.0)]);
+}
@@ -1,80 +1,315 @@
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use crate::{fractal, machine};
+use image::ImageResult;
+use crate::image_writer;
+use crate::machine::Machine;
+use crate::viewer::Viewer;
+use rusty_fractals_result::result_data;
 use rusty_fractals_common::area::Area;
+use rusty_fractals_common::constants::ZOOM;
+use rusty_fractals_domain::domain;
 use rusty_fractals_domain::domain::Domain;
-use crate::fractal::{AppConfig, CalculationConfig, ResultConfig};
+use crate::camera::CameraPath;
+use crate::fractal;
+use crate::fractal_stats;
+use crate::fractal::{AppConfig, CalculationConfig, Math, MemType, PathSelection, ResultConfig};
+#[cfg(test)]
+use crate::fixture;
 
 // to calculate zoom, sequence of images
+// Domain area moves together with result area, it keeps its margin around the result area
 pub struct Engine<'lif> {
-    pub area: &'lif Area,
-    pub domain: &'lif Domain<'lif>,
+    pub area: &'lif mut Area,
+    pub result_area: &'lif mut Area,
+    pub domain: &'lif mut Domain,
     pub calculation_config: CalculationConfig,
     pub app_config: AppConfig,
     pub result_config: ResultConfig,
+    pub viewer: Viewer,
 }
 
-impl Engine<'_>  {
-    pub fn calculate(&self) {
-        /*
-        let mut first = true;
+impl Engine<'_> {
+    pub fn calculate<T: MemType>(&mut self, fractal_math: &impl Math<T>) -> ImageResult<()> {
+        // paths of domain elements calculated in previous frames
+        let mut result_data = result_data::init();
+        let image_writer = image_writer::init(&self.app_config);
+        let mut stats = fractal_stats::init();
 
         for it in 1.. {
             println!("{}", it);
 
-            if first {
-                first = false;
-                self.domain.init_domain_elements();
-            } else {
-                self.domain.recalculate_pixels_positions_for_this_zoom();
+            if it > 1 {
+                // only divergent paths skip the Mandelbrot interior
+                let hibernate = self.calculation_config.path_selection == PathSelection::Divergent;
+                let counts = self.domain.recalculate_pixels_positions_for_this_zoom(self.area, hibernate);
+                stats.domain_elements_hibernated = counts.hibernated;
+                stats.domain_elements_created = counts.created;
             }
 
-            let coordinates_xy = self.domain.shuffled_calculation_coordinates();
-
-            // Calculate independently and in parallel each domain chunks
-            coordinates_xy.into_par_iter().for_each(
-                |xy| machine::chunk_calculation(&self.domain, xy)
-            );
+            // calculate domain, translate paths to pixels, perfectly color, save
+            let machine = Machine {
+                area: self.area,
+                result_area: self.result_area,
+                domain: self.domain,
+                calculation_config: self.calculation_config,
+                app_config: self.app_config.clone(),
+                result_config: self.result_config.clone(),
+            };
+            let viewer = &mut self.viewer;
+            let (result_image, domain_image) = machine.calculate_frame_with_progress(fractal_math, &mut result_data, &mut stats, &mut |result_image, domain_image| viewer.repaint(result_image, domain_image));
+            self.viewer.repaint(&result_image, &domain_image);
+            if self.app_config.save_images {
+                image_writer.save_images(&result_image, &domain_image, it)?;
+            }
 
+            if self.zoom_finished(it) {
+                break;
+            }
+            fractal::update(&mut self.calculation_config, &mut stats, it);
+            let margin = self.margin();
+            if self.viewer.update_target() {
+                // target is clicked in result image
+                self.result_area.move_to_coordinates(&self.viewer.target);
+            }
+            self.result_area.zoom_in();
+            if self.app_config.rotation != 0.0 {
+                self.result_area.rotate(self.app_config.rotation);
+            }
+            self.follow_result_area(margin);
+            if let Some([re, im]) = self.area.check_precision() {
+                // area switched to double-double precision, coordinates are relative to the new origin
+                self.result_area.move_origin(re, im);
+                result_data.shift(re, im);
+                self.domain.shift(re, im);
+            }
+            result_data.remove_elements_outside(self.result_area);
+        }
+        Ok(())
+    }
 
-            PathsFinebrot.translatePathsToPixelGrid();
-            MaskMandelbrot.maskFullUpdate();
+    // Sequence of images along camera path, AppConfig.frames long
+    // Area, iteration limits and palette are taken from the camera for each frame, each frame is calculated from scratch
+    // Frames after the last keyframe keep its view
+    pub fn calculate_camera_path<T: MemType>(&mut self, fractal_math: &impl Math<T>, camera: &CameraPath) -> ImageResult<()> {
+        let image_writer = image_writer::init(&self.app_config);
+        let margin = self.margin();
 
-            fractal.perfectly_color_values();
-            Application.repaint_mandelbrot_window();
+        if self.app_config.frames != camera.frames() {
+            println!("Warning: camera path has {} frames, calculating {} frames", camera.frames(), self.app_config.frames);
+        }
+        for it in 1..=self.app_config.frames {
+            println!("{}", it);
+            let frame = camera.frame(it);
+            let (re, im) = self.result_area.to_relative(frame.center_re, frame.center_im);
+            self.result_area.set_view(re, im, frame.width_re, frame.rotation);
+            self.follow_result_area(margin);
+            if let Some([re, im]) = self.area.check_precision() {
+                // nothing is kept from previous frames, only result area follows the origin
+                self.result_area.move_origin(re, im);
+            }
+            self.calculation_config.iteration_min = frame.iteration_min;
+            self.calculation_config.iteration_max = frame.iteration_max;
+            self.result_config.palette = frame.palette;
+            *self.domain = domain::init(self.area, self.domain.resolution_multiplier);
 
-            if SAVE_IMAGES {
-                FractalImages.saveMandelbrotImages();
+            let machine = Machine {
+                area: self.area,
+                result_area: self.result_area,
+                domain: self.domain,
+                calculation_config: self.calculation_config,
+                app_config: self.app_config.clone(),
+                result_config: self.result_config.clone(),
+            };
+            let viewer = &mut self.viewer;
+            let (result_image, domain_image) = machine.calculate_image_with_progress(fractal_math, &mut fractal_stats::init(), &mut |result_image, domain_image| viewer.repaint(result_image, domain_image));
+            self.viewer.repaint(&result_image, &domain_image);
+            if self.app_config.save_images {
+                image_writer.save_images(&result_image, &domain_image, it)?;
             }
 
-            fractal.update();
-            // image_pixels.clear()
-            Application.zoomIn();
+            if !self.viewer.is_open() {
+                break;
+            }
         }
-        */
+        Ok(())
+    }
+
+    // how many times is domain area wider than result area
+    fn margin(&self) -> f64 {
+        self.area.width_re / self.result_area.width_re
     }
+
+    // domain area is centered at result area, rotated the same way and keeps its margin
+    fn follow_result_area(&mut self, margin: f64) {
+        let result_area = &self.result_area;
+        self.area.set_view(result_area.center_re, result_area.center_im, result_area.width_re * margin, result_area.rotation());
+    }
+
+    fn zoom_finished(&self, it: u32) -> bool {
+        !self.app_config.repeat
+            || it >= self.app_config.frames
+            || self.area.width_re * ZOOM < self.app_config.width_re_min
+            || !self.viewer.is_open()
+    }
+}
+
+#[test]
+fn test_zoom_finished() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
+    use crate::fixture::engine;
+    use crate::viewer;
+
+    let area_config = area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 20, height_y: 20, rotation: 0.0 };
+    let mut area = area::init(area_config);
+    let mut result_area = area::init(area_config);
+    let mut domain = domain::init(&area, ResolutionMultiplier::None);
+    let engine = Engine {
+        app_config: AppConfig { frames: 10, width_re_min: 0.5, ..fixture::zoom_app_config() },
+        ..engine(&mut area, &mut result_area, &mut domain, viewer::init_headless(20, 20))
+    };
+
+    assert!(!engine.zoom_finished(1));
+    assert!(engine.zoom_finished(10));
 }
 
-fn run() {
-    /*
-    for el in maskMandelbrotElementsPart {
-        // Investigate calculation path for each mandelbrot pixel
-        final ArrayList < double
-        [] > path = finebrotFractal.calculatePath(el);
-        if path != null {
-            // Removed lastIteration, lastVisitedRe, lastVisitedIm
-            // There isn't continuation of unfinished iteration from previous calculation (ITERATION_MAX increased)
-            // The element and its path is going to migrate out of screen soon.
-
-            PathsFinebrot.addEscapePathLong(path);
+#[test]
+fn test_calculate_zooms_to_clicked_target() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
+    use crate::fixture::{engine, TEST};
+    use crate::viewer;
+    use crate::viewer::HeadlessBackend;
+
+    let area_config = area::AreaConfig { width_x: 40, height_y: 40, ..fixture::area_config() };
+    let mut area = area::init(area_config);
+    let mut result_area = area::init(area_config);
+    let (target_re, target_im) = (result_area.screen_to_domain_re(5, 30), result_area.screen_to_domain_im(5, 30));
+    let mut domain = domain::init(&area, ResolutionMultiplier::None);
+    let mut engine = Engine {
+        app_config: AppConfig { frames: 2, ..fixture::zoom_app_config() },
+        ..engine(&mut area, &mut result_area, &mut domain, viewer::init(Box::new(HeadlessBackend::new(vec![(45, 30)])), 40, 40))
+    };
+
+    engine.calculate(&TEST).unwrap();
+
+    assert_eq!(engine.viewer.image.dimensions(), (80, 40));
+    assert_eq!((engine.result_area.center_re, engine.result_area.center_im), (target_re, target_im));
+    assert_eq!((engine.area.center_re, engine.area.center_im), (target_re, target_im));
+}
+
+#[test]
+fn test_calculate_rotates_area() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
+    use crate::fixture::{engine, TEST};
+    use crate::viewer;
+
+    let area_config = area::AreaConfig { width_x: 40, height_y: 40, ..fixture::area_config() };
+    let mut area = area::init(area_config);
+    let mut result_area = area::init(area_config);
+    let mut domain = domain::init(&area, ResolutionMultiplier::None);
+    let mut engine = Engine {
+        app_config: AppConfig { frames: 3, rotation: 0.1, ..fixture::zoom_app_config() },
+        ..engine(&mut area, &mut result_area, &mut domain, viewer::init_headless(40, 40))
+    };
+
+    engine.calculate(&TEST).unwrap();
+
+    assert!((engine.result_area.rotation() - 0.2).abs() < 1e-12);
+    assert_eq!(engine.area.rotation(), engine.result_area.rotation());
+    // elements moved to the rotated area are at pixels of their coordinates
+    for (x, column) in engine.domain.domain_elements.iter().enumerate() {
+        for (y, el) in column.iter().enumerate() {
+            assert_eq!(engine.area.domain_point_to_result_pixel(el.origin_re, el.origin_im), (x, y));
         }
     }
-    if lastMandelbrotRefresh + 97 < currentTimeMillis() {
-        // Handle refresh with calculation progress for all the threads
+}
 
-        lastMandelbrotRefresh = currentTimeMillis();
+#[test]
+fn test_calculate_camera_path() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
+    use rusty_fractals_result::palettes::{palette_black_to_white, palette_blue_to_white};
+    use crate::camera;
+    use crate::camera::{Easing, Keyframe};
+    use crate::fixture::{engine, TEST};
+    use crate::viewer;
+    use rusty_fractals_common::double_double::DoubleDouble;
 
-        MaskMandelbrot.maskFullUpdate();
-        Application.repaintMaskMandelbrotWindow();
-    }
-    */
+    let camera = camera::init(vec![
+        Keyframe { frame: 1, center_re: DoubleDouble::from(-0.5), center_im: DoubleDouble::ZERO, width_re: 3.5, rotation: 0.0, iteration_min: 3, iteration_max: 100, palette: palette_black_to_white(), easing: Easing::EaseInOut },
+        Keyframe { frame: 3, center_re: DoubleDouble::from(-0.1), center_im: DoubleDouble::from(-0.9), width_re: 0.5, rotation: 0.3, iteration_min: 5, iteration_max: 300, palette: palette_blue_to_white(), easing: Easing::Linear },
+    ]);
+    let area_config = area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 40, height_y: 40, rotation: 0.0 };
+    let mut area = area::init(area_config);
+    let mut result_area = area::init(area_config);
+    let mut domain = domain::init(&area, ResolutionMultiplier::None);
+    let mut engine = Engine {
+        app_config: AppConfig { frames: 3, ..fixture::zoom_app_config() },
+        ..engine(&mut area, &mut result_area, &mut domain, viewer::init_headless(40, 40))
+    };
+
+    engine.calculate_camera_path(&TEST, &camera).unwrap();
+
+    // the last keyframe
+    assert_eq!((engine.result_area.center_re, engine.result_area.center_im, engine.result_area.width_re), (-0.1, -0.9, 0.5));
+    assert_eq!(engine.result_area.rotation(), 0.3);
+    assert_eq!((engine.area.center_re, engine.area.center_im, engine.area.rotation()), (-0.1, -0.9, 0.3));
+    assert_eq!(engine.calculation_config.iteration_max, 300);
+    assert_eq!(engine.result_config.palette.spectrum, palette_blue_to_white().spectrum);
+    // domain of the last frame
+    assert_eq!(engine.domain.domain_elements[20][20].origin_re, engine.area.screen_to_domain_re(20, 20));
+}
+
+#[test]
+fn test_calculate_keeps_margin() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
+    use crate::fixture::{engine, TEST};
+    use crate::viewer;
+
+    // pixels of the same size, domain area has margin of 10 pixels on each side
+    let result_config = area::AreaConfig { width_x: 40, height_y: 40, ..fixture::area_config() };
+    let mut area = area::init(area::AreaConfig { width_re: 3.5 * 1.5, width_x: 60, height_y: 60, ..result_config });
+    let mut result_area = area::init(result_config);
+    let mut domain = domain::init(&area, ResolutionMultiplier::None);
+    let mut engine = Engine {
+        app_config: AppConfig { frames: 3, rotation: 0.1, ..fixture::zoom_app_config() },
+        ..engine(&mut area, &mut result_area, &mut domain, viewer::init_headless(40, 40))
+    };
+
+    engine.calculate(&TEST).unwrap();
+
+    // both areas zoomed in twice, domain area is still 1.5 times wider
+    assert!((engine.result_area.width_re - 3.5 * ZOOM * ZOOM).abs() < 1e-12);
+    assert!((engine.area.width_re / engine.result_area.width_re - 1.5).abs() < 1e-12);
+    assert_eq!((engine.area.center_re, engine.area.center_im), (engine.result_area.center_re, engine.result_area.center_im));
+    assert_eq!(engine.area.rotation(), engine.result_area.rotation());
+}
+
+#[test]
+fn test_calculate_camera_path_frames() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
+    use rusty_fractals_result::palettes::palette_black_to_white;
+    use crate::camera;
+    use crate::camera::{Easing, Keyframe};
+    use crate::fixture::{engine, TEST};
+    use crate::viewer;
+    use rusty_fractals_common::double_double::DoubleDouble;
+
+    let keyframe = |frame: u32, width_re: f64| Keyframe { frame, center_re: DoubleDouble::from(-0.5), center_im: DoubleDouble::ZERO, width_re, rotation: 0.0, iteration_min: 3, iteration_max: 100, palette: palette_black_to_white(), easing: Easing::Linear };
+    let camera = camera::init(vec![keyframe(1, 3.5), keyframe(5, 0.35)]);
+    let area_config = area::AreaConfig { width_x: 20, height_y: 20, ..fixture::area_config() };
+    let mut area = area::init(area_config);
+    let mut result_area = area::init(area_config);
+    let mut domain = domain::init(&area, ResolutionMultiplier::None);
+    let mut engine = Engine {
+        app_config: AppConfig { frames: 1, ..fixture::zoom_app_config() },
+        ..engine(&mut area, &mut result_area, &mut domain, viewer::init_headless(20, 20))
+    };
+
+    engine.calculate_camera_path(&TEST, &camera).unwrap();
+
+    // AppConfig.frames ends the path at its first frame
+    assert_eq!(engine.result_area.width_re, 3.5);
 }
@@ -0,0 +1,92 @@
+// Shared configuration of Machine and Engine tests
+// Tests override only the fields they check, by struct update of machine() or engine()
+
+use std::sync::Arc;
+use rusty_fractals_common::area;
+use rusty_fractals_common::area::Area;
+use rusty_fractals_common::double_double::DoubleDouble;
+use rusty_fractals_domain::domain::Domain;
+use rusty_fractals_result::coloring_strategy::Perfect;
+use rusty_fractals_result::palettes::palette_black_to_white;
+use rusty_fractals_result::result_pixels::Splatting;
+use crate::engine::Engine;
+use crate::fractal::{AppConfig, CalculationConfig, FractalType, Math, MathDD, PathSelection, ResultConfig};
+use crate::machine::Machine;
+use crate::mem::Mem;
+use crate::mem_dd::MemDD;
+use crate::viewer::Viewer;
+
+// z² + c, with the deep zoom arithmetics switched on by the flags
+pub struct Test {
+    pub double_double: bool,
+    pub perturbation: bool,
+}
+
+pub const TEST: Test = Test { double_double: false, perturbation: false };
+
+impl Math<Mem> for Test {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+
+    fn double_double(&self) -> Option<&dyn MathDD> {
+        if self.double_double { Some(self) } else { None }
+    }
+
+    fn perturbation(&self) -> bool {
+        self.perturbation
+    }
+}
+
+impl MathDD for Test {
+    fn math_dd(&self, m: &mut MemDD, origin_re: DoubleDouble, origin_im: DoubleDouble) {
+        m.square();
+        m.plus(origin_re, origin_im);
+    }
+}
+
+// the whole Mandelbrot set
+pub fn area_config() -> area::AreaConfig {
+    area::AreaConfig { width_re: 3.5, center_re: -0.5, center_im: 0.0, width_x: 100, height_y: 100, rotation: 0.0 }
+}
+
+pub fn calculation_config() -> CalculationConfig {
+    CalculationConfig { fractal_type: FractalType::Finebrot, path_selection: PathSelection::Divergent, iteration_min: 3, iteration_max: 200 }
+}
+
+pub fn app_config() -> AppConfig {
+    AppConfig { name: "Test".to_string(), repeat: false, save_images: false, output_dir: "/tmp/".to_string(), frames: 1, width_re_min: 0.0, rotation: 0.0 }
+}
+
+// Engine repeats calculation for each frame of zoom
+pub fn zoom_app_config() -> AppConfig {
+    AppConfig { repeat: true, ..app_config() }
+}
+
+pub fn result_config() -> ResultConfig {
+    ResultConfig { palette: palette_black_to_white(), palette_zero: None, bands: Vec::new(), coloring: Arc::new(Perfect {}), splatting: Splatting::Nearest }
+}
+
+pub fn machine<'a>(area: &'a Area, domain: &'a Domain) -> Machine<'a> {
+    Machine {
+        area,
+        result_area: area,
+        domain,
+        calculation_config: calculation_config(),
+        app_config: app_config(),
+        result_config: result_config(),
+    }
+}
+
+pub fn engine<'a>(area: &'a mut Area, result_area: &'a mut Area, domain: &'a mut Domain, viewer: Viewer) -> Engine<'a> {
+    Engine {
+        area,
+        result_area,
+        domain,
+        calculation_config: calculation_config(),
+        app_config: zoom_app_config(),
+        result_config: result_config(),
+        viewer,
+    }
+}
@@ -1,46 +1,187 @@
+use std::sync::Arc;
+use rusty_fractals_result::coloring_strategy::ColoringStrategy;
 use rusty_fractals_result::palette::Palette;
+use rusty_fractals_result::result_pixels::Splatting;
+use rusty_fractals_common::double_double::DoubleDouble;
 use fractal_stats::Stats;
-use rusty_fractals_domain::resolution_multiplier;
 use crate::{fractal_stats};
+use crate::mem_dd::MemDD;
 
+// Finebrot: Nebula like fractals, calculation paths of divergent origins are drawn
+// Mandelbrot: static escape time fractals, each pixel is colored by iteration count of its origin
+// Nebulabrot: Finebrot paths split by their length to layers, each layer is colored by its own palette
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FractalType { Finebrot, Mandelbrot, Nebulabrot }
+
+// Which calculation paths of Finebrot fractals are recorded
+// Divergent: paths of origins outside of Mandelbrot set, Nebula fractals
+// NonDivergent: paths of origins inside of Mandelbrot set, which never escape, Infinity (anti-Buddhabrot) fractals
+// Both: all paths
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PathSelection { Divergent, NonDivergent, Both }
+
+impl PathSelection {
+    // path of length iterator is recorded
+    pub fn records(&self, iterator: u32, iteration_max: u32) -> bool {
+        match self {
+            PathSelection::Divergent => iterator < iteration_max,
+            PathSelection::NonDivergent => iterator == iteration_max,
+            PathSelection::Both => true,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct CalculationConfig {
+    pub fractal_type: FractalType,
+    pub path_selection: PathSelection,
     pub iteration_min: u32,
     pub iteration_max: u32,
 }
 
+// Nebulabrot layer, paths calculated from iteration_from (inclusive) to iteration_to (exclusive) iterations
+#[derive(Clone)]
+pub struct Band {
+    pub iteration_from: u32,
+    pub iteration_to: u32,
+    pub palette: Palette,
+}
+
+impl Band {
+    pub fn contains(&self, iterator: u32) -> bool {
+        self.iteration_from <= iterator && iterator < self.iteration_to
+    }
+}
+
+#[derive(Clone)]
 pub struct ResultConfig {
     pub palette: Palette,
+    // insides of Mandelbrot set, black if None
+    pub palette_zero: Option<Palette>,
+    // layers of Nebulabrot, empty for other fractal types
+    pub bands: Vec<Band>,
+    // how are values of Finebrot and Nebulabrot pixels turned into palette colors
+    pub coloring: Arc<dyn ColoringStrategy>,
+    // how are path points added to result pixels
+    pub splatting: Splatting,
 }
 
+#[derive(Clone)]
 pub struct AppConfig {
+    // fractal name, used in names of saved images
+    pub name: String,
+    // calculate zoom, sequence of images
     pub repeat: bool,
     pub save_images: bool,
+    // save images to this directory, created if missing
+    pub output_dir: String,
+    // zoom stops after this many frames
+    pub frames: u32,
+    // or when area zoomed in to this width
+    pub width_re_min: f64,
+    // area rotates by this angle with each zoom step, in radians
+    pub rotation: f64,
 }
 
-pub trait Math<T> {
+// Sync, fractal math is shared by all the threads calculating domain chunks in parallel
+pub trait Math<T: MemType>: Sync {
     fn math(&self, m: &mut T, origin_re: f64, origin_im: f64);
+
+    // Memory object for calculation from origin
+    // Override for fractals with additional initial state, like Phoenix c and p
+    fn new_mem(&self, origin_re: f64, origin_im: f64) -> T {
+        T::new(origin_re, origin_im)
+    }
+
+    // Math in double-double precision, for deep zoom, None if fractal has none
+    // Machine uses it when area switched to double-double precision, otherwise deep zoom is calculated in f64 and pixels collapse
+    fn double_double(&self) -> Option<&dyn MathDD> {
+        None
+    }
+
+    // Math is z² + c, deep zoom is calculated by perturbation of single reference orbit, faster than math_dd
+    fn perturbation(&self) -> bool {
+        false
+    }
+}
+
+// The same math as Math, in double-double precision
+// Fractals opt in to deep zoom by returning themselves from Math::double_double()
+pub trait MathDD: Sync {
+    fn math_dd(&self, m: &mut MemDD, origin_re: DoubleDouble, origin_im: DoubleDouble);
+
+    // Memory object for calculation from origin, the same initial state as Math::new_mem
+    fn new_mem_dd(&self, origin_re: DoubleDouble, origin_im: DoubleDouble) -> MemDD {
+        MemDD::new(origin_re, origin_im)
+    }
 }
 
-/*
-pub fn update(mut stats: Stats) {
-    // TODO ITERATION_MAX += 150;
+// Memory object of any fractal type, carries calculation results
+pub trait MemType: Sized {
+    fn new(re: f64, im: f64) -> Self;
+    fn re(&self) -> f64;
+    fn im(&self) -> f64;
+    fn quad(&self) -> f64;
+    // start calculation again from origin, fractal specific initial state is preserved
+    fn reset(&mut self, re: f64, im: f64);
+}
 
-    stats.update(0); // TODO
+// Adapt iteration limits between zoom frames, so that deep zoom frames don't fade out
+pub fn update(calculation_config: &mut CalculationConfig, stats: &mut Stats, it: u32) {
+    stats.update(it);
 
     if stats.not_enough_pixels_best_value {
-        // ("increase ITERATION_MAX, not enough Points");
-        // TODO ITERATION_MAX += 20_000;
+        println!("increase iteration_max, not enough points");
+        calculation_config.iteration_max += 20_000;
     }
     if stats.less_pixels_best_value {
-        // TODO ITERATION_MAX += 2_000;
-        // ("increase ITERATION_MAX, bit less Points");
+        println!("increase iteration_max, bit less points");
+        calculation_config.iteration_max += 2_000;
     }
     if stats.too_many_paths_total {
-        // ("increase a bit ITERATION_MIN, too many paths total");
-        // TODO ITERATION_min += 1;
+        println!("increase a bit iteration_min, too many paths total");
+        calculation_config.iteration_min += 1;
     }
 
     stats.print();
     stats.clean();
 }
-*/
+
+#[test]
+fn test_update() {
+    let mut calculation_config = CalculationConfig { fractal_type: FractalType::Finebrot, path_selection: PathSelection::Divergent, iteration_min: 42, iteration_max: 14800 };
+    let mut stats = fractal_stats::init();
+
+    // nothing measured yet, limits stay
+    update(&mut calculation_config, &mut stats, 1);
+    assert_eq!(calculation_config.iteration_max, 14800);
+    assert_eq!(calculation_config.iteration_min, 42);
+
+    stats.not_enough_pixels_best_value = true;
+    stats.less_pixels_best_value = true;
+    stats.too_many_paths_total = true;
+    update(&mut calculation_config, &mut stats, 2);
+    assert_eq!(calculation_config.iteration_max, 36800);
+    assert_eq!(calculation_config.iteration_min, 43);
+}
+
+#[test]
+fn test_path_selection_records() {
+    assert!(PathSelection::Divergent.records(199, 200));
+    assert!(!PathSelection::Divergent.records(200, 200));
+    assert!(!PathSelection::NonDivergent.records(199, 200));
+    assert!(PathSelection::NonDivergent.records(200, 200));
+    assert!(PathSelection::Both.records(199, 200));
+    assert!(PathSelection::Both.records(200, 200));
+}
+
+#[test]
+fn test_band_contains() {
+    use rusty_fractals_result::palettes::palette_black_to_white;
+
+    let band = Band { iteration_from: 42, iteration_to: 500, palette: palette_black_to_white() };
+    assert!(!band.contains(41));
+    assert!(band.contains(42));
+    assert!(band.contains(499));
+    assert!(!band.contains(500));
+}
@@ -1,17 +1,24 @@
 use rusty_fractals_common::constants::TAKE_MEASURES_AT_FRAME;
 
-pub(crate) struct Stats {
-    new_elements_too_long: i32,
-    new_elements_too_short: i32,
-    new_elements_long: i32,
+// Counters are filled by Machine for each frame, measures are taken at TAKE_MEASURES_AT_FRAME
+#[derive(Default)]
+pub struct Stats {
+    pub new_elements_too_long: u64,
+    pub new_elements_too_short: u64,
+    pub new_elements_long: u64,
+    // good paths not recorded, because of PATHS_POINTS_MAX
+    pub new_elements_dropped: u64,
+    // new domain elements after zoom, hibernated ones aren't calculated
+    pub domain_elements_hibernated: u64,
+    pub domain_elements_created: u64,
 
     // All paths including previous calculations
     // The amount of newly added paths is not the same as the amount of red elementLong
-    paths_total_amount: i32,
+    pub paths_total_amount: u64,
 
-    paths_new_points_amount: i32,
-    pixels_value_total: i32,
-    pixels_value_best: i32,
+    pub paths_new_points_amount: u64,
+    pub pixels_value_total: u64,
+    pub pixels_value_best: u64,
 
     not_enough_pixels_total_value: bool,
     less_pixels_total_value: bool,
@@ -21,15 +28,15 @@ pub(crate) struct Stats {
     pub too_many_paths_total: bool,
     not_enough_long_elements: bool,
 
-    new_elements_long_measure: i32,
-    new_elements_long_tolerance: i32,
-    paths_total_amount_measure: i32,
-    paths_total_amount_tolerance: i32,
-    pixels_value_total_measure: i32,
-    pixels_value_total_tolerance: i32,
-    pixels_value_best_measure: i32,
-    pixels_value_best_tolerance: i32,
-    average_path_length_measure: i32,
+    new_elements_long_measure: u64,
+    new_elements_long_tolerance: u64,
+    paths_total_amount_measure: u64,
+    paths_total_amount_tolerance: u64,
+    pixels_value_total_measure: u64,
+    pixels_value_total_tolerance: u64,
+    pixels_value_best_measure: u64,
+    pixels_value_best_tolerance: u64,
+    average_path_length_measure: u64,
 }
 
 impl Stats {
@@ -42,13 +49,13 @@ impl Stats {
         self.pixels_value_total_measure = self.pixels_value_total;
         self.paths_total_amount_measure = self.paths_total_amount;
         self.average_path_length_measure =
-            (self.pixels_value_total as f64 / self.paths_total_amount as f64) as i32;
-        // TODO self.pixels_value_best_measure = PixelsFinebrot.bestFourChunksValue();
+            (self.pixels_value_total as f64 / self.paths_total_amount as f64) as u64;
+        self.pixels_value_best_measure = self.pixels_value_best;
 
-        self.new_elements_long_tolerance = (self.new_elements_long_measure as f64 * 0.5) as i32;
-        self.pixels_value_total_tolerance = (self.pixels_value_total_measure as f64 * 0.5) as i32;
-        self.paths_total_amount_tolerance = (self.paths_total_amount_measure as f64 * 0.5) as i32;
-        self.pixels_value_best_tolerance = (self.pixels_value_best_measure as f64 * 0.5) as i32;
+        self.new_elements_long_tolerance = (self.new_elements_long_measure as f64 * 0.5) as u64;
+        self.pixels_value_total_tolerance = (self.pixels_value_total_measure as f64 * 0.5) as u64;
+        self.paths_total_amount_tolerance = (self.paths_total_amount_measure as f64 * 0.5) as u64;
+        self.pixels_value_best_tolerance = (self.pixels_value_best_measure as f64 * 0.5) as u64;
 
         println!("elementsLong_measure        {} ", self.new_elements_long_measure);
         println!("pixels_value_total_measure  {} ", self.pixels_value_total_measure);
@@ -57,7 +64,16 @@ impl Stats {
         println!("average_path_length_measure {} ", self.average_path_length_measure);
     }
 
-    pub(crate) fn update(&mut self, it: u32) {
+    // add counters of other (thread local) stats to this one
+    pub fn merge(&mut self, other: &Stats) {
+        self.new_elements_too_long += other.new_elements_too_long;
+        self.new_elements_too_short += other.new_elements_too_short;
+        self.new_elements_long += other.new_elements_long;
+        self.new_elements_dropped += other.new_elements_dropped;
+        self.paths_new_points_amount += other.paths_new_points_amount;
+    }
+
+    pub fn update(&mut self, it: u32) {
         // Check if Stats should remember this iteration data for subsequent comparison
         if it == TAKE_MEASURES_AT_FRAME {
             self.remember_this();
@@ -78,7 +94,6 @@ impl Stats {
 
             // Best domain chunks, chunks with most image points
             self.not_enough_pixels_best_value = false;
-            // TODO self.pixels_value_best = PixelsFinebrot.bestFourChunksValue();
             if self.pixels_value_best < self.pixels_value_best_measure {
                 self.not_enough_pixels_best_value = self.pixels_value_best_measure - self.pixels_value_best > self.pixels_value_best_tolerance;
             }
@@ -116,6 +131,9 @@ impl Stats {
         self.new_elements_too_long = 0;
         self.new_elements_too_short = 0;
         self.new_elements_long = 0;
+        self.new_elements_dropped = 0;
+        self.domain_elements_hibernated = 0;
+        self.domain_elements_created = 0;
         self.paths_total_amount = 0;
         self.pixels_value_total = 0;
         self.pixels_value_best = 0;
@@ -126,9 +144,51 @@ impl Stats {
         println!("new_elements_too_long   {}", self.new_elements_too_long);
         println!("new_elements_too_short  {}", self.new_elements_too_short);
         println!("new_elements_long       {}", self.new_elements_long);
+        println!("new_elements_dropped    {}", self.new_elements_dropped);
+        println!("domain_elements_hibernated {}", self.domain_elements_hibernated);
+        println!("domain_elements_created    {}", self.domain_elements_created);
         println!("paths_total_amount      {}", self.paths_total_amount);
         println!("pixels_value_total      {}", self.pixels_value_total);
         println!("pixels_value_best       {}", self.pixels_value_best);
         println!("paths_new_points_amount {}", self.paths_new_points_amount);
     }
 }
+
+pub fn init() -> Stats {
+    Stats::default()
+}
+
+#[test]
+fn test_merge() {
+    let mut stats = init();
+    stats.new_elements_long = 2;
+    let mut other = init();
+    other.new_elements_long = 3;
+    other.new_elements_too_short = 1;
+    other.new_elements_dropped = 2;
+    other.paths_new_points_amount = 40;
+
+    stats.merge(&other);
+
+    assert_eq!(stats.new_elements_long, 5);
+    assert_eq!(stats.new_elements_too_short, 1);
+    assert_eq!(stats.new_elements_dropped, 2);
+    assert_eq!(stats.paths_new_points_amount, 40);
+}
+
+#[test]
+fn test_update() {
+    let mut stats = init();
+    stats.pixels_value_best = 1000;
+    stats.paths_total_amount = 100;
+    stats.update(TAKE_MEASURES_AT_FRAME);
+    stats.clean();
+
+    stats.pixels_value_best = 400;
+    stats.paths_total_amount = 160;
+    stats.update(TAKE_MEASURES_AT_FRAME + 1);
+
+    assert!(stats.less_pixels_best_value);
+    assert!(stats.not_enough_pixels_best_value);
+    assert!(stats.too_many_paths_total);
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use image::{ImageResult, RgbImage};
+use crate::fractal::AppConfig;
+
+// Saves result and domain images of each frame as
+// <output_dir>/<fractal_name>_<timestamp>_<frame>.png
+// <output_dir>/<fractal_name>_<timestamp>_<frame>_domain.png
+// Timestamp is the same for all frames of one run, so these are sorted by run and then by frame.
+pub struct ImageWriter {
+    pub output_dir: PathBuf,
+    pub file_prefix: String,
+}
+
+impl ImageWriter {
+    pub fn save_images(&self, result_image: &RgbImage, domain_image: &RgbImage, frame: u32) -> ImageResult<()> {
+        self.save_result_image(result_image, frame)?;
+        self.save_domain_image(domain_image, frame)?;
+        Ok(())
+    }
+
+    pub fn save_result_image(&self, result_image: &RgbImage, frame: u32) -> ImageResult<PathBuf> {
+        self.save(result_image, self.file_name(frame, ""))
+    }
+
+    pub fn save_domain_image(&self, domain_image: &RgbImage, frame: u32) -> ImageResult<PathBuf> {
+        self.save(domain_image, self.file_name(frame, "_domain"))
+    }
+
+    fn save(&self, image: &RgbImage, file_name: String) -> ImageResult<PathBuf> {
+        // io errors, like not writable directory, are returned as ImageError::IoError
+        fs::create_dir_all(&self.output_dir)?;
+        let path = self.output_dir.join(file_name);
+        image.save(&path)?;
+        println!("saved {}", path.display());
+        Ok(path)
+    }
+
+    fn file_name(&self, frame: u32, suffix: &str) -> String {
+        format!("{}_{:05}{}.png", self.file_prefix, frame, suffix)
+    }
+}
+
+pub fn init(app_config: &AppConfig) -> ImageWriter {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    ImageWriter {
+        output_dir: PathBuf::from(&app_config.output_dir),
+        file_prefix: format!("{}_{}", file_name_of(&app_config.name), timestamp),
+    }
+}
+
+// "Nebula top" -> "nebula_top"
+fn file_name_of(fractal_name: &str) -> String {
+    fractal_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+#[test]
+fn test_file_name() {
+    let writer = ImageWriter { output_dir: PathBuf::from("/Fractals/"), file_prefix: format!("{}_{}", file_name_of("Nebula top"), 1700000000) };
+
+    assert_eq!(writer.file_name(7, ""), "nebula_top_1700000000_00007.png");
+    assert_eq!(writer.file_name(7, "_domain"), "nebula_top_1700000000_00007_domain.png");
+}
+
+#[test]
+fn test_save_creates_output_dir() {
+    let output_dir = std::env::temp_dir().join(format!("rusty_fractals_test_{}", std::process::id())).join("zoom");
+    let writer = ImageWriter { output_dir: output_dir.clone(), file_prefix: "test".to_string() };
+
+    let path = writer.save_result_image(&RgbImage::new(4, 3), 1).unwrap();
+
+    assert_eq!(path, output_dir.join("test_00001.png"));
+    assert_eq!(image::open(&path).unwrap().width(), 4);
+    fs::remove_dir_all(output_dir.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_save_returns_error() {
+    // output directory can't be created under a regular file
+    let file = std::env::temp_dir().join(format!("rusty_fractals_test_file_{}", std::process::id()));
+    fs::write(&file, "").unwrap();
+    let writer = ImageWriter { output_dir: file.join("zoom"), file_prefix: "test".to_string() };
+
+    let result = writer.save_domain_image(&RgbImage::new(4, 3), 1);
+
+    assert!(result.is_err());
+    fs::remove_file(file).unwrap();
+}
@@ -1,12 +1,20 @@
 pub mod engine;
+pub mod camera;
 pub mod machine;
 pub mod fractal;
 pub mod fractal_stats;
 pub mod mathematician;
 pub mod mem;
+pub mod mem_dd;
+pub mod perturbation;
 pub mod mem_collatz;
 pub mod mem_phoenix;
+pub mod mem_euler;
 pub mod fractal_path;
+pub mod image_writer;
+pub mod viewer;
+#[cfg(test)]
+mod fixture;
 
 fn lib() {
     let cores: usize = num_cpus::get();
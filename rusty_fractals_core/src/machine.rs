@@ -1,111 +1,1231 @@
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use rusty_fractals_result::{perfect_color_distribution, result_pixels};
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::Duration;
+use image::{ImageResult, RgbImage};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rusty_fractals_result::{coloring_strategy, perfect_color_distribution, result_data, result_data_static, result_pixels};
 use rusty_fractals_result::result_data::ResultData;
+use rusty_fractals_result::result_data_static::ResultDataStatic;
+use rusty_fractals_result::palette::Palette;
 use rusty_fractals_result::result_pixels::ResultPixels;
-use rusty_fractals_common::area::Area;
-use rusty_fractals_common::constants::CALCULATION_BOUNDARY;
+use rusty_fractals_common::area::{Area, Precision};
+use rusty_fractals_common::double_double::DoubleDouble;
+use rusty_fractals_common::constants::{CALCULATION_BOUNDARY, CHUNKS, PATHS_POINTS_MAX, REFRESH_MILLIS};
 use rusty_fractals_domain::domain::Domain;
 use rusty_fractals_domain::domain_element::DomainElement;
 use rusty_fractals_domain::pixel_states::DomainElementState;
-use crate::fractal::{AppConfig, CalculationConfig, Math, ResultConfig};
-use crate::mem::Mem;
+use rusty_fractals_domain::resolution_multiplier::ResolutionMultiplier;
+use crate::fractal::{AppConfig, CalculationConfig, FractalType, Math, MathDD, MemType, PathSelection, ResultConfig};
+use crate::fractal_stats;
+use crate::fractal_stats::Stats;
+#[cfg(test)]
+use crate::fixture;
+use crate::image_writer;
+use crate::mem_dd::MemDD;
+use crate::perturbation;
+use crate::perturbation::{PerturbedOrbit, ReferenceOrbit};
 
 // to calculate single image
+// Origins of paths are seeded over domain area, only points inside of result area are recorded
+// Both areas are the same for most fractals, result area can be a detail of domain area
+// Coordinates of both areas are relative to the same origin
 pub struct Machine<'lif> {
     pub area: &'lif Area,
-    pub domain: &'lif Domain<'lif>,
+    pub result_area: &'lif Area,
+    pub domain: &'lif Domain,
     pub calculation_config: CalculationConfig,
     pub app_config: AppConfig,
     pub result_config: ResultConfig,
 }
 
+// Where are good calculation paths recorded
+// Paths: kept as [re,im] points, zoom projects them again to pixels of next frames
+// Pixels: added right away to shared result pixels, no paths are kept. One layer for each Nebulabrot band, or a single layer
+// PixelsAndPaths: added right away to result pixels, and kept as points for next frames while points_left last
+// Spectra: added right away to shared result pixels, each point to layer of spectrum_of(point index, path length), Euler fractal
+pub enum Record<'a> {
+    Paths { result: &'a mut ResultData, points_left: &'a AtomicUsize },
+    Pixels(&'a [ResultPixels]),
+    PixelsAndPaths { layers: &'a [ResultPixels], result: &'a mut ResultData, points_left: &'a AtomicUsize },
+    Spectra { layers: &'a [ResultPixels], spectrum_of: &'a (dyn Fn(u32, u32) -> usize + Sync) },
+}
+
+// How are orbits calculated
+// Double: f64, area isn't zoomed in beyond its precision
+// DoubleDouble: double-double precision math of fractal
+// Perturbation: f64 difference from double-double reference orbit, z² + c only
+#[derive(Clone, Copy)]
+pub enum Arithmetic<'a> {
+    Double,
+    DoubleDouble(&'a dyn MathDD),
+    Perturbation(&'a ReferenceOrbit),
+}
+
 impl Machine<'_> {
-    pub fn calculate(&mut self, fractal_math: &impl Math<Mem>) {
+    pub fn calculate<T: MemType>(&mut self, fractal_math: &impl Math<T>) -> ImageResult<()> {
+        let mut stats = fractal_stats::init();
+        let (result_image, domain_image) = self.calculate_image(fractal_math, &mut stats);
+        if self.app_config.save_images {
+            image_writer::init(&self.app_config).save_images(&result_image, &domain_image, 1)?;
+        }
+        Ok(())
+    }
+
+    // Single image, there are no next frames, calculation paths are added to result pixels right away
+    pub fn calculate_image<T: MemType>(&self, fractal_math: &impl Math<T>, stats: &mut Stats) -> (RgbImage, RgbImage) {
+        self.calculate_image_with_progress(fractal_math, stats, &mut |_, _| {})
+    }
+
+    // Unfinished result image and domain image are passed to refresh every REFRESH_MILLIS
+    pub fn calculate_image_with_progress<T: MemType>(&self, fractal_math: &impl Math<T>, stats: &mut Stats, refresh: &mut impl FnMut(&RgbImage, &RgbImage)) -> (RgbImage, RgbImage) {
+        self.check_config();
+        match self.calculation_config.fractal_type {
+            FractalType::Mandelbrot => self.calculate_frame_mandelbrot(fractal_math),
+            FractalType::Finebrot | FractalType::Nebulabrot => {
+                let layers = self.init_layers();
+                let interval = Duration::from_millis(REFRESH_MILLIS);
+                self.with_progress(&layers, interval, || self.calculate_pixels(fractal_math, &layers, stats), refresh);
+
+                stats.paths_total_amount = stats.new_elements_long;
+                stats.pixels_value_total = stats.paths_new_points_amount;
+
+                self.color_layers(&layers, stats)
+            }
+        }
+    }
+
+    // Returns perfectly colored result image and domain image, stats are filled with this frame values
+    pub fn calculate_frame<T: MemType>(&self, fractal_math: &impl Math<T>, result_data: &mut ResultData, stats: &mut Stats) -> (RgbImage, RgbImage) {
+        self.calculate_frame_with_progress(fractal_math, result_data, stats, &mut |_, _| {})
+    }
+
+    // Unfinished result image and domain image are passed to refresh every REFRESH_MILLIS
+    pub fn calculate_frame_with_progress<T: MemType>(&self, fractal_math: &impl Math<T>, result_data: &mut ResultData, stats: &mut Stats, refresh: &mut impl FnMut(&RgbImage, &RgbImage)) -> (RgbImage, RgbImage) {
+        self.check_config();
+        match self.calculation_config.fractal_type {
+            FractalType::Finebrot | FractalType::Nebulabrot => self.calculate_frame_paths(fractal_math, result_data, stats, refresh),
+            FractalType::Mandelbrot => self.calculate_frame_mandelbrot(fractal_math),
+        }
+    }
+
+    // Project paths from previous zoom frames to pixels, and add new domain elements to pixels as they are calculated
+    // Zoom can't re-project pixels, new paths are kept in result data for next frames
+    // All kept paths together have at most PATHS_POINTS_MAX points
+    // Nebulabrot paths are split to layers by their calculation length
+    fn calculate_frame_paths<T: MemType>(&self, fractal_math: &impl Math<T>, result_data: &mut ResultData, stats: &mut Stats, refresh: &mut impl FnMut(&RgbImage, &RgbImage)) -> (RgbImage, RgbImage) {
         println!("calculate()");
-        let coordinates_xy = self.domain.shuffled_calculation_coordinates();
+        let layers = self.init_layers();
+        let previous_total: u64 = result_data.paths
+            .par_iter()
+            .zip(result_data.iterations.par_iter())
+            .map(|(path, iterator)| match self.layer_of(&layers, *iterator) {
+                Some(layer) => layer.translate_path_to_pixel_grid(path, self.result_area),
+                None => 0,
+            })
+            .sum();
+
+        let kept_points: usize = result_data.paths.iter().map(|path| path.len()).sum();
+        let mut frame_stats = fractal_stats::init();
+        let points_left = PATHS_POINTS_MAX.saturating_sub(kept_points);
+        let interval = Duration::from_millis(REFRESH_MILLIS);
+        let new_paths = self.with_progress(&layers, interval, || self.calculate_pixels_and_paths(fractal_math, &layers, points_left, &mut frame_stats), refresh);
+
+        stats.merge(&frame_stats);
+        stats.paths_total_amount = result_data.paths.len() as u64 + frame_stats.new_elements_long;
+        stats.pixels_value_total = previous_total + frame_stats.paths_new_points_amount;
+        result_data.merge(new_paths);
+
+        self.color_layers(&layers, stats)
+    }
 
-        let mut result_data = ResultData {
-            paths: Vec::new()
+    // Calculation runs in its own thread, this thread colors unfinished layers and passes them to refresh every interval
+    fn with_progress<R: Send>(&self, layers: &[ResultPixels], interval: Duration, calculation: impl FnOnce() -> R + Send, refresh: &mut impl FnMut(&RgbImage, &RgbImage)) -> R {
+        thread::scope(|scope| {
+            let (finished, done) = mpsc::channel();
+            let calculation = scope.spawn(move || {
+                let result = calculation();
+                // nobody waits, if refresh panicked
+                let _ = finished.send(());
+                result
+            });
+            // panicked calculation disconnects the channel
+            while let Err(RecvTimeoutError::Timeout) = done.recv_timeout(interval) {
+                let (result_image, domain_image) = self.paint_layers(layers);
+                refresh(&result_image, &domain_image);
+            }
+            calculation.join().unwrap_or_else(|e| panic::resume_unwind(e))
+        })
+    }
+
+    // Origins are seeded in domain area, only paths which reach into result area are recorded
+    // Nebulabrot paints one layer for each band, there is nothing to paint without bands
+    fn check_config(&self) {
+        assert!(self.area.covers(self.result_area), "result area isn't inside of domain area");
+        if self.calculation_config.fractal_type == FractalType::Nebulabrot {
+            assert!(!self.result_config.bands.is_empty(), "Nebulabrot needs at least one band");
+        }
+    }
+
+    fn init_layers(&self) -> Vec<ResultPixels> {
+        let count = match self.calculation_config.fractal_type {
+            FractalType::Nebulabrot => self.result_config.bands.len(),
+            _ => 1,
         };
+        (0..count)
+            .map(|_| {
+                let mut layer = result_pixels::init(self.result_area.width_x, self.result_area.height_y);
+                layer.splatting = self.result_config.splatting;
+                layer
+            })
+            .collect()
+    }
+
+    // Nebulabrot layer of band containing the calculation length, None if there isn't such band
+    fn layer_of<'a>(&self, layers: &'a [ResultPixels], iterator: u32) -> Option<&'a ResultPixels> {
+        match self.calculation_config.fractal_type {
+            FractalType::Nebulabrot => self.result_config.bands.iter().position(|band| band.contains(iterator)).map(|i| &layers[i]),
+            _ => layers.first(),
+        }
+    }
 
-        // Calculate independently and in parallel each domain chunks
-        // TODO coordinates_xy.into_par_iter().for_each(
-        coordinates_xy.iter().for_each(
-            |xy| self.chunk_calculation(&xy, fractal_math, &mut result_data)
-        );
+    // Nebulabrot layers are colored each by palette of its band
+    fn color_layers(&self, layers: &[ResultPixels], stats: &mut Stats) -> (RgbImage, RgbImage) {
+        stats.pixels_value_best = layers.iter().map(|layer| layer.best_four_chunks_value()).sum();
+        self.paint_layers(layers)
+    }
+
+    // also layers of unfinished calculation
+    fn paint_layers(&self, layers: &[ResultPixels]) -> (RgbImage, RgbImage) {
+        let domain_image = self.domain.domain_element_states_to_image();
 
-        let mut result_pixels = result_pixels::init(self.area.width_x, self.area.height_y);
+        let coloring = self.result_config.coloring.as_ref();
+        let result_image = match self.calculation_config.fractal_type {
+            FractalType::Nebulabrot => {
+                let palettes: Vec<&Palette> = self.result_config.bands.iter().map(|band| &band.palette).collect();
+                coloring_strategy::color_layers(coloring, layers, &palettes)
+            }
+            _ => coloring.color(&layers[0], &self.result_config.palette),
+        };
+
+        (result_image, domain_image)
+    }
 
-        result_pixels.translate_paths_to_pixel_grid(result_data.paths, &self.area);
+    // Static fractal, each pixel is calculated again for each frame
+    fn calculate_frame_mandelbrot<T: MemType>(&self, fractal_math: &impl Math<T>) -> (RgbImage, RgbImage) {
+        println!("calculate_mandelbrot()");
+        let result_data = self.calculate_static(fractal_math);
 
         let domain_image = self.domain.domain_element_states_to_image();
 
-        let result_image = perfect_color_distribution::perfectly_color_result_values(&result_pixels, &self.result_config.palette);
+        let result_image = perfect_color_distribution::perfectly_color_values_mandelbrot(&result_data, &self.result_config.palette, self.result_config.palette_zero.as_ref());
 
-        // TODO Application.repaint_mandelbrot_window();
+        (result_image, domain_image)
     }
 
-    // in sequence (cpu_num) executes as 20x20 parallel for each domain chunk
-    pub fn chunk_calculation(&self, xy: &[u32; 2], fractal_math: &impl Math<Mem>, result: &mut ResultData) {
-        let chunk_size_x = (self.domain.width / 20) as u32;
-        let chunk_size_y = (self.domain.height / 20) as u32;
+    // Calculate in parallel iteration count and final quad for each pixel
+    pub fn calculate_static<T: MemType>(&self, fractal_math: &impl Math<T>) -> ResultDataStatic {
+        let reference = self.reference_orbit(fractal_math);
+        let arithmetic = self.arithmetic(fractal_math, reference.as_ref());
+        let mut result_data = result_data_static::init(self.area.width_x, self.area.height_y);
+        result_data.pixels
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(x, column)| {
+                for (y, el) in column.iter_mut().enumerate() {
+                    let origin_re = self.area.screen_to_domain_re(x, y);
+                    let origin_im = self.area.screen_to_domain_im(x, y);
+                    let (iterator, quad) = self.calculate_escape(origin_re, origin_im, fractal_math, arithmetic);
+                    el.set_finished_state(iterator, quad, self.calculation_config.iteration_max);
+                }
+            });
+        result_data
+    }
+
+    fn calculate_escape<T: MemType>(&self, origin_re: f64, origin_im: f64, fractal_math: &impl Math<T>, arithmetic: Arithmetic) -> (u32, f64) {
+        match arithmetic {
+            Arithmetic::Double => self.calculate_escape_orbit(&mut OrbitF64::new(self.area, origin_re, origin_im, fractal_math)),
+            Arithmetic::DoubleDouble(math_dd) => self.calculate_escape_orbit(&mut OrbitDD::new(self.area, origin_re, origin_im, math_dd)),
+            Arithmetic::Perturbation(reference) => self.calculate_escape_orbit(&mut reference.orbit(origin_re, origin_im)),
+        }
+    }
 
-        let x_from = (xy[0] * chunk_size_x) as usize;
-        let x_to = ((xy[0] + 1) * chunk_size_x) as usize;
-        let y_from = (xy[1] * chunk_size_y) as usize;
-        let y_to = ((xy[1] + 1) * chunk_size_y) as usize;
+    fn calculate_escape_orbit(&self, orbit: &mut impl Orbit) -> (u32, f64) {
+        let max = self.calculation_config.iteration_max;
+        let cb = CALCULATION_BOUNDARY as f64;
+        let mut iterator = 0;
+        while orbit.quad() < cb && iterator < max {
+            orbit.next();
+            iterator += 1;
+        }
+        (iterator, orbit.quad())
+    }
+
+    // Area zoomed in beyond f64 precision, z² + c is calculated by perturbation of reference orbit
+    fn reference_orbit<T: MemType>(&self, fractal_math: &impl Math<T>) -> Option<ReferenceOrbit> {
+        if self.area.precision() == Precision::DoubleDouble && fractal_math.perturbation() {
+            Some(perturbation::init(self.area, self.calculation_config.iteration_max))
+        } else {
+            None
+        }
+    }
+
+    // Decided once for each frame, by area precision and by what fractal math supports
+    fn arithmetic<'a, T: MemType>(&self, fractal_math: &'a impl Math<T>, reference: Option<&'a ReferenceOrbit>) -> Arithmetic<'a> {
+        if let Some(reference) = reference {
+            return Arithmetic::Perturbation(reference);
+        }
+        if self.area.precision() == Precision::Double {
+            return Arithmetic::Double;
+        }
+        if let Some(math_dd) = fractal_math.double_double() {
+            return Arithmetic::DoubleDouble(math_dd);
+        }
+        println!("Warning: area is zoomed in beyond f64 precision, but fractal math has no double-double precision");
+        Arithmetic::Double
+    }
+
+    // Calculate independently and in parallel each domain chunks
+    // Each thread collects calculation paths and stats into its own result data and stats, these are merged at the end
+    // All threads share the amount of path points, which can still be recorded
+    pub fn calculate_paths<T: MemType>(&self, fractal_math: &impl Math<T>, stats: &mut Stats) -> ResultData {
+        let reference = self.reference_orbit(fractal_math);
+        let arithmetic = self.arithmetic(fractal_math, reference.as_ref());
+        let coordinates_xy = self.domain.shuffled_calculation_coordinates();
+        let points_left = AtomicUsize::new(PATHS_POINTS_MAX);
+
+        let (result, chunk_stats) = coordinates_xy
+            .into_par_iter()
+            .fold(
+                || (result_data::init(), fractal_stats::init()),
+                |(mut result, mut stats), xy| {
+                    self.chunk_calculation(&xy, fractal_math, arithmetic, &mut Record::Paths { result: &mut result, points_left: &points_left }, &mut stats);
+                    (result, stats)
+                },
+            )
+            .reduce(
+                || (result_data::init(), fractal_stats::init()),
+                |(mut result, mut stats), (other, other_stats)| {
+                    result.merge(other);
+                    stats.merge(&other_stats);
+                    (result, stats)
+                },
+            );
+        stats.merge(&chunk_stats);
+        if chunk_stats.new_elements_dropped > 0 {
+            println!("paths points limit reached, {} paths dropped", chunk_stats.new_elements_dropped);
+        }
+        result
+    }
+
+    // Calculate independently and in parallel each domain chunks
+    // All threads add good paths to the same result pixels, only stats are thread local
+    pub fn calculate_pixels<T: MemType>(&self, fractal_math: &impl Math<T>, layers: &[ResultPixels], stats: &mut Stats) {
+        let reference = self.reference_orbit(fractal_math);
+        let arithmetic = self.arithmetic(fractal_math, reference.as_ref());
+        let coordinates_xy = self.domain.shuffled_calculation_coordinates();
+
+        let chunk_stats = coordinates_xy
+            .into_par_iter()
+            .fold(
+                fractal_stats::init,
+                |mut stats, xy| {
+                    self.chunk_calculation(&xy, fractal_math, arithmetic, &mut Record::Pixels(layers), &mut stats);
+                    stats
+                },
+            )
+            .reduce(
+                fractal_stats::init,
+                |mut stats, other_stats| {
+                    stats.merge(&other_stats);
+                    stats
+                },
+            );
+        stats.merge(&chunk_stats);
+    }
+
+    // Calculate independently and in parallel each domain chunks
+    // All threads add points of good paths to the same spectra layers, only stats are thread local
+    // Index of point counts only points inside of result area, so does path length
+    pub fn calculate_spectra<T: MemType>(&self, fractal_math: &impl Math<T>, layers: &[ResultPixels], spectrum_of: &(dyn Fn(u32, u32) -> usize + Sync), stats: &mut Stats) {
+        let reference = self.reference_orbit(fractal_math);
+        let arithmetic = self.arithmetic(fractal_math, reference.as_ref());
+        let coordinates_xy = self.domain.shuffled_calculation_coordinates();
+
+        let chunk_stats = coordinates_xy
+            .into_par_iter()
+            .fold(
+                fractal_stats::init,
+                |mut stats, xy| {
+                    self.chunk_calculation(&xy, fractal_math, arithmetic, &mut Record::Spectra { layers, spectrum_of }, &mut stats);
+                    stats
+                },
+            )
+            .reduce(
+                fractal_stats::init,
+                |mut stats, other_stats| {
+                    stats.merge(&other_stats);
+                    stats
+                },
+            );
+        stats.merge(&chunk_stats);
+    }
+
+    // Calculate independently and in parallel each domain chunks
+    // All threads add good paths to the same result pixels, each thread keeps its paths and stats, these are merged at the end
+    // All threads share the amount of path points, which can still be kept
+    pub fn calculate_pixels_and_paths<T: MemType>(&self, fractal_math: &impl Math<T>, layers: &[ResultPixels], points_left: usize, stats: &mut Stats) -> ResultData {
+        let reference = self.reference_orbit(fractal_math);
+        let arithmetic = self.arithmetic(fractal_math, reference.as_ref());
+        let coordinates_xy = self.domain.shuffled_calculation_coordinates();
+        let points_left = AtomicUsize::new(points_left);
+
+        let (result, chunk_stats) = coordinates_xy
+            .into_par_iter()
+            .fold(
+                || (result_data::init(), fractal_stats::init()),
+                |(mut result, mut stats), xy| {
+                    self.chunk_calculation(&xy, fractal_math, arithmetic, &mut Record::PixelsAndPaths { layers, result: &mut result, points_left: &points_left }, &mut stats);
+                    (result, stats)
+                },
+            )
+            .reduce(
+                || (result_data::init(), fractal_stats::init()),
+                |(mut result, mut stats), (other, other_stats)| {
+                    result.merge(other);
+                    stats.merge(&other_stats);
+                    (result, stats)
+                },
+            );
+        stats.merge(&chunk_stats);
+        if chunk_stats.new_elements_dropped > 0 {
+            println!("paths points limit reached, {} paths not kept for next frames", chunk_stats.new_elements_dropped);
+        }
+        result
+    }
+
+    // in sequence (cpu_num) executes as CHUNKS x CHUNKS parallel for each domain chunk
+    pub fn chunk_calculation<T: MemType>(&self, xy: &[u32; 2], fractal_math: &impl Math<T>, arithmetic: Arithmetic, record: &mut Record, stats: &mut Stats) {
+        let chunk_size_x = self.domain.width / CHUNKS as usize;
+        let chunk_size_y = self.domain.height / CHUNKS as usize;
+        let (cx, cy) = (xy[0] as usize, xy[1] as usize);
+
+        // the last chunks take the remainder pixels
+        let x_from = cx * chunk_size_x;
+        let x_to = if xy[0] == CHUNKS - 1 { self.domain.width } else { (cx + 1) * chunk_size_x };
+        let y_from = cy * chunk_size_y;
+        let y_to = if xy[1] == CHUNKS - 1 { self.domain.height } else { (cy + 1) * chunk_size_y };
         for x in x_from..x_to {
             for y in y_from..y_to {
                 let core_element: &DomainElement = self.domain.domain_elements[x]
                     .get(y)
                     .expect("domain_elements problem");
                 if core_element.is_active_new() {
-                    self.calculate_path_finite(core_element, fractal_math, result);
+                    let state = self.calculate_path_finite(core_element, fractal_math, arithmetic, record, stats);
+                    // Sub-pixel origins of ResolutionMultiplier.
+                    // Don't wrap elements on the side of Mandelbrot set boundary, which isn't recorded
+                    let wrap = match self.calculation_config.path_selection {
+                        PathSelection::Divergent => state != DomainElementState::FinishedTooLong,
+                        PathSelection::NonDivergent => state == DomainElementState::FinishedTooLong,
+                        PathSelection::Both => true,
+                    };
+                    if self.domain.resolution_multiplier != ResolutionMultiplier::None && wrap {
+                        for wrapped in self.domain.wrap(core_element, self.area) {
+                            self.calculate_path_finite(&wrapped, fractal_math, arithmetic, record, stats);
+                        }
+                    }
                 }
             }
         }
     }
 
-    pub fn calculate_path_finite(&self, el: &DomainElement, fractal_math: &impl Math<Mem>, result: &mut ResultData) -> DomainElementState {
+    pub fn calculate_path_finite<T: MemType>(&self, el: &DomainElement, fractal_math: &impl Math<T>, arithmetic: Arithmetic, record: &mut Record, stats: &mut Stats) -> DomainElementState {
+        let (re, im) = (el.origin_re, el.origin_im);
+        match arithmetic {
+            Arithmetic::Double => self.calculate_path_orbit(el, &mut OrbitF64::new(self.area, re, im, fractal_math), record, stats),
+            Arithmetic::DoubleDouble(math_dd) => self.calculate_path_orbit(el, &mut OrbitDD::new(self.area, re, im, math_dd), record, stats),
+            Arithmetic::Perturbation(reference) => self.calculate_path_orbit(el, &mut reference.orbit(re, im), record, stats),
+        }
+    }
+
+    fn calculate_path_orbit(&self, el: &DomainElement, orbit: &mut impl Orbit, record: &mut Record, stats: &mut Stats) -> DomainElementState {
         let max = self.calculation_config.iteration_max;
         let min = self.calculation_config.iteration_min;
         let cb = CALCULATION_BOUNDARY as f64;
         let mut iterator = 0;
         let mut length = 0;
-        let mut m = Mem::new(el.origin_re, el.origin_im);
-        while m.quad() < cb && iterator < max {
+        while orbit.quad() < cb && iterator < max {
 
             // Investigate if this is a good calculation path
             // Don't create path data yet. Too many origins don't produce good data
             // Most of the long and expensive calculations end up inside Mandelbrot set, useless
             // It is 1.68x faster to calculate path twice, and recording exclusively the good paths
 
-            fractal_math.math(&mut m, el.origin_re, el.origin_im);
-            if self.area.contains(m.re, m.im) {
+            orbit.next();
+            let (re, im) = orbit.re_im();
+            if self.result_area.contains(re, im) {
                 length += 1;
             }
             iterator += 1;
         }
         let el_state = Domain::state_from_path_length(iterator, max, min);
 
-        if length > min && iterator < max {
+        let good_path = length > min && self.calculation_config.path_selection.records(iterator, max);
+
+        let mut dropped = false;
+        if good_path {
+            match record {
+                Record::Paths { points_left, .. } if !reserve(points_left, length as usize) => {
+                    // Memory safeguard, too many points were recorded already
+                    stats.new_elements_dropped += 1;
+                }
+                Record::Paths { result, .. } => {
+
+                    // This origin produced good data, record calculation path
+                    // Path length is known, allocate it at once, non-divergent paths are very long
 
-            // This origin produced good data, record calculation path
+                    orbit.reset();
+                    el.good_path();
+
+                    let mut path: Vec<[f64; 2]> = Vec::with_capacity(length as usize);
+                    for _ in 0..iterator {
+                        orbit.next();
+                        let (re, im) = orbit.re_im();
+                        if self.result_area.contains(re, im) {
+                            path.push([re, im]);
+                        }
+                    }
+                    stats.paths_new_points_amount += path.len() as u64;
+                    stats.new_elements_long += 1;
+                    result.add_calculation_path(path, iterator);
+                }
+                Record::Pixels(layers) => {
+                    // Nebulabrot paths out of all bands aren't recorded
+                    if let Some(layer) = self.layer_of(layers, iterator) {
+                        orbit.reset();
+                        el.good_path();
+
+                        for _ in 0..iterator {
+                            orbit.next();
+                            let (re, im) = orbit.re_im();
+                            layer.add_point(re, im, self.result_area);
+                        }
+                        stats.paths_new_points_amount += length as u64;
+                        stats.new_elements_long += 1;
+                    }
+                }
+                Record::PixelsAndPaths { layers, result, points_left } => {
+                    if let Some(layer) = self.layer_of(layers, iterator) {
+                        orbit.reset();
+                        el.good_path();
 
-            let mut m = Mem::new(el.origin_re, el.origin_im);
-            // TODO el.good_path();
+                        // Memory safeguard, path which can't be kept is still added to pixels of this frame
+                        // Its element stays active new, it is calculated again for the next frame
+                        let keep = reserve(points_left, length as usize);
+                        dropped = !keep;
+                        let mut path: Vec<[f64; 2]> = Vec::with_capacity(if keep { length as usize } else { 0 });
+                        for _ in 0..iterator {
+                            orbit.next();
+                            let (re, im) = orbit.re_im();
+                            if layer.add_point(re, im, self.result_area) && keep {
+                                path.push([re, im]);
+                            }
+                        }
+                        stats.paths_new_points_amount += length as u64;
+                        stats.new_elements_long += 1;
+                        if keep {
+                            result.add_calculation_path(path, iterator);
+                        } else {
+                            stats.new_elements_dropped += 1;
+                        }
+                    }
+                }
+                Record::Spectra { layers, spectrum_of } => {
+                    orbit.reset();
+                    el.good_path();
 
-            let mut path: Vec<[f64; 2]> = Vec::new();
-            for _ in 0..iterator {
-                fractal_math.math(&mut m, el.origin_re, el.origin_im);
-                if self.area.contains(m.re, m.im) {
-                    path.push([m.re, m.im]);
+                    let mut index = 0;
+                    for _ in 0..iterator {
+                        orbit.next();
+                        let (re, im) = orbit.re_im();
+                        if self.result_area.contains(re, im) {
+                            layers[spectrum_of(index, length)].add_point(re, im, self.result_area);
+                            index += 1;
+                        }
+                    }
+                    stats.paths_new_points_amount += length as u64;
+                    stats.new_elements_long += 1;
                 }
             }
-            result.add_calculation_path(path);
-            // stats.paths_new_points_amount += path.size();
+        }
+        match el_state {
+            DomainElementState::FinishedTooLong => stats.new_elements_too_long += 1,
+            DomainElementState::FinishedTooShort => stats.new_elements_too_short += 1,
+            _ => {}
         }
 
+        if dropped {
+            el.set_finished_state(DomainElementState::ActiveNew);
+        } else {
+            el.set_finished_state(el_state);
+        }
         el_state
     }
 }
+
+// Calculation from single origin, in f64 or in double-double precision
+// Coordinates of orbit points are relative to area origin
+trait Orbit {
+    // one iteration of fractal math
+    fn next(&mut self);
+    fn quad(&self) -> f64;
+    fn re_im(&self) -> (f64, f64);
+    // start again from origin
+    fn reset(&mut self);
+}
+
+struct OrbitF64<'a, T: MemType, M: Math<T>> {
+    m: T,
+    fractal_math: &'a M,
+    // absolute origin, and area origin, both are [0, 0] until f64 isn't precise enough
+    origin_re: f64,
+    origin_im: f64,
+    area_re: f64,
+    area_im: f64,
+}
+
+impl<'a, T: MemType, M: Math<T>> OrbitF64<'a, T, M> {
+    fn new(area: &Area, re: f64, im: f64, fractal_math: &'a M) -> Self {
+        let (area_re, area_im) = (area.origin_re.to_f64(), area.origin_im.to_f64());
+        let (origin_re, origin_im) = (area_re + re, area_im + im);
+        OrbitF64 { m: fractal_math.new_mem(origin_re, origin_im), fractal_math, origin_re, origin_im, area_re, area_im }
+    }
+}
+
+impl<T: MemType, M: Math<T>> Orbit for OrbitF64<'_, T, M> {
+    fn next(&mut self) {
+        self.fractal_math.math(&mut self.m, self.origin_re, self.origin_im);
+    }
+
+    fn quad(&self) -> f64 {
+        self.m.quad()
+    }
+
+    fn re_im(&self) -> (f64, f64) {
+        (self.m.re() - self.area_re, self.m.im() - self.area_im)
+    }
+
+    fn reset(&mut self) {
+        self.m.reset(self.origin_re, self.origin_im);
+    }
+}
+
+impl Orbit for PerturbedOrbit<'_> {
+    fn next(&mut self) {
+        self.next();
+    }
+
+    fn quad(&self) -> f64 {
+        self.quad()
+    }
+
+    fn re_im(&self) -> (f64, f64) {
+        self.re_im()
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+}
+
+struct OrbitDD<'a> {
+    m: MemDD,
+    math_dd: &'a dyn MathDD,
+    area: &'a Area,
+    origin_re: DoubleDouble,
+    origin_im: DoubleDouble,
+}
+
+impl<'a> OrbitDD<'a> {
+    fn new(area: &'a Area, re: f64, im: f64, math_dd: &'a dyn MathDD) -> Self {
+        let (origin_re, origin_im) = area.to_absolute(re, im);
+        OrbitDD { m: math_dd.new_mem_dd(origin_re, origin_im), math_dd, area, origin_re, origin_im }
+    }
+}
+
+impl Orbit for OrbitDD<'_> {
+    fn next(&mut self) {
+        self.math_dd.math_dd(&mut self.m, self.origin_re, self.origin_im);
+    }
+
+    fn quad(&self) -> f64 {
+        self.m.quad()
+    }
+
+    fn re_im(&self) -> (f64, f64) {
+        self.area.to_relative(self.m.re, self.m.im)
+    }
+
+    fn reset(&mut self) {
+        self.m = self.math_dd.new_mem_dd(self.origin_re, self.origin_im);
+    }
+}
+
+// Take amount of points from points left, false if there isn't enough of them
+fn reserve(points_left: &AtomicUsize, amount: usize) -> bool {
+    points_left
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| left.checked_sub(amount))
+        .is_ok()
+}
+
+#[test]
+fn test_calculate_paths_parallel_same_as_sequential() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+
+    // calculation stores element states, each run needs its own domain
+    let parallel_domain = domain::init(&area, ResolutionMultiplier::None);
+    let sequential_domain = domain::init(&area, ResolutionMultiplier::None);
+
+    let parallel = machine(&area, &parallel_domain).calculate_paths(&TEST, &mut fractal_stats::init());
+
+    let mut sequential = result_data::init();
+    let sequential_machine = machine(&area, &sequential_domain);
+    for xy in sequential_domain.shuffled_calculation_coordinates() {
+        sequential_machine.chunk_calculation(&xy, &TEST, Arithmetic::Double, &mut Record::Paths { result: &mut sequential, points_left: &AtomicUsize::new(PATHS_POINTS_MAX) }, &mut fractal_stats::init());
+    }
+
+    assert_eq!(parallel.paths.len(), sequential.paths.len());
+
+    let parallel_pixels = result_pixels::init(area.width_x, area.height_y);
+    parallel_pixels.translate_paths_to_pixel_grid(&parallel.paths, &area);
+    let sequential_pixels = result_pixels::init(area.width_x, area.height_y);
+    sequential_pixels.translate_paths_to_pixel_grid(&sequential.paths, &area);
+
+    assert!(parallel_pixels.values().eq(sequential_pixels.values()));
+}
+
+#[test]
+fn test_calculate_all_domain_elements() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    // size isn't divisible by CHUNKS, the last chunks take the remainder
+    let area = area::init(area::AreaConfig { width_x: 105, height_y: 103, ..fixture::area_config() });
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+
+    machine(&area, &domain).calculate(&TEST).unwrap();
+
+    assert!(domain.domain_elements.iter().flatten().all(|el| !el.is_active_new()));
+}
+
+#[test]
+fn test_chunk_calculation_resolution_multiplier() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let calculate = |resolution_multiplier| {
+        let domain = domain::init(&area, resolution_multiplier);
+        machine(&area, &domain).calculate_paths(&TEST, &mut fractal_stats::init()).paths.len()
+    };
+
+    let single = calculate(ResolutionMultiplier::None);
+    assert!(single > 0);
+    assert!(calculate(ResolutionMultiplier::SquareAlter) > single);
+    assert!(calculate(ResolutionMultiplier::Square3) > calculate(ResolutionMultiplier::SquareAlter));
+}
+
+#[test]
+fn test_calculate_paths_stores_element_states() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+
+    let mut stats = fractal_stats::init();
+    let result = machine(&area, &domain).calculate_paths(&TEST, &mut stats);
+
+    let states: Vec<DomainElementState> = domain.domain_elements.iter().flatten().map(|el| el.state()).collect();
+    let successful = states.iter().filter(|s| **s == DomainElementState::FinishedSuccess).count();
+    assert!(!result.paths.is_empty());
+    assert!(successful >= result.paths.len());
+    assert!(states.contains(&DomainElementState::FinishedTooLong));
+    assert!(states.contains(&DomainElementState::FinishedTooShort));
+    assert_eq!(stats.new_elements_long as usize, result.paths.len());
+    assert_eq!(stats.new_elements_too_long as usize, states.iter().filter(|s| **s == DomainElementState::FinishedTooLong).count());
+    assert_eq!(stats.paths_new_points_amount as usize, result.paths.iter().map(|p| p.len()).sum::<usize>());
+}
+
+#[test]
+fn test_calculate_paths_non_divergent() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = Machine {
+        calculation_config: CalculationConfig { path_selection: PathSelection::NonDivergent, ..fixture::calculation_config() },
+        ..machine(&area, &domain)
+    };
+
+    let mut stats = fractal_stats::init();
+    let result = machine.calculate_paths(&TEST, &mut stats);
+
+    // all origins inside of Mandelbrot set are recorded, their orbits stay mostly in the area
+    assert!(!result.paths.is_empty());
+    assert_eq!(result.paths.len() as u64, stats.new_elements_too_long);
+    assert!(result.paths.iter().all(|path| path.len() > 3 && path.len() <= 200));
+    assert_eq!(stats.new_elements_dropped, 0);
+}
+
+#[test]
+fn test_calculate_frame_nebulabrot() {
+    use image::Rgb;
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+    use crate::fractal::Band;
+
+    let red = Palette { spectrum: (0..8).map(|i| Rgb([i * 30, 0, 0])).collect() };
+    let blue = Palette { spectrum: (0..8).map(|i| Rgb([0, 0, i * 30])).collect() };
+    let area = area::init(fixture::area_config());
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = Machine {
+        calculation_config: CalculationConfig { fractal_type: FractalType::Nebulabrot, ..fixture::calculation_config() },
+        result_config: ResultConfig {
+            bands: vec![
+                Band { iteration_from: 3, iteration_to: 20, palette: red },
+                Band { iteration_from: 20, iteration_to: 200, palette: blue },
+            ],
+            ..fixture::result_config()
+        },
+        ..machine(&area, &domain)
+    };
+
+    let mut result_data = result_data::init();
+    let mut stats = fractal_stats::init();
+    let (result_image, _) = machine.calculate_frame(&TEST, &mut result_data, &mut stats);
+
+    assert_eq!(result_data.paths.len(), result_data.iterations.len());
+    assert!(result_data.iterations.iter().any(|it| *it < 20));
+    assert!(result_data.iterations.iter().any(|it| *it >= 20));
+    assert!(stats.pixels_value_total > 0);
+    // both layers are painted, no green in any of them
+    assert!(result_image.pixels().any(|p| p[0] > 0));
+    assert!(result_image.pixels().any(|p| p[2] > 0));
+    assert!(result_image.pixels().all(|p| p[1] == 0));
+}
+
+#[test]
+fn test_calculate_pixels_same_as_paths() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let paths_domain = domain::init(&area, ResolutionMultiplier::None);
+    let pixels_domain = domain::init(&area, ResolutionMultiplier::None);
+
+    let mut paths_stats = fractal_stats::init();
+    let paths = machine(&area, &paths_domain).calculate_paths(&TEST, &mut paths_stats);
+    let paths_pixels = result_pixels::init(area.width_x, area.height_y);
+    let points = paths_pixels.translate_paths_to_pixel_grid(&paths.paths, &area);
+
+    // no paths are kept, points are added by calculation threads directly
+    let mut pixels_stats = fractal_stats::init();
+    let pixels_machine = machine(&area, &pixels_domain);
+    let pixels = pixels_machine.init_layers();
+    pixels_machine.calculate_pixels(&TEST, &pixels, &mut pixels_stats);
+
+    assert_eq!(pixels.len(), 1);
+    assert!(points > 0);
+    assert_eq!(pixels_stats.new_elements_long, paths_stats.new_elements_long);
+    assert_eq!(pixels_stats.paths_new_points_amount, paths_stats.paths_new_points_amount);
+    assert!(pixels[0].values().eq(paths_pixels.values()));
+}
+
+#[test]
+fn test_calculate_spectra() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let paths_domain = domain::init(&area, ResolutionMultiplier::None);
+    let spectra_domain = domain::init(&area, ResolutionMultiplier::None);
+
+    // the same points, split to layers by their index in path
+    let paths = machine(&area, &paths_domain).calculate_paths(&TEST, &mut fractal_stats::init());
+    let expected: Vec<ResultPixels> = (0..3).map(|_| result_pixels::init(area.width_x, area.height_y)).collect();
+    for path in &paths.paths {
+        for (i, p) in path.iter().enumerate() {
+            expected[i % 3].add_point(p[0], p[1], &area);
+        }
+    }
+
+    let layers: Vec<ResultPixels> = (0..3).map(|_| result_pixels::init(area.width_x, area.height_y)).collect();
+    let mut stats = fractal_stats::init();
+    machine(&area, &spectra_domain).calculate_spectra(&TEST, &layers, &|index, _| index as usize % 3, &mut stats);
+
+    assert_eq!(stats.new_elements_long as usize, paths.paths.len());
+    for (layer, expected) in layers.iter().zip(&expected) {
+        assert!(layer.values().any(|v| v > 0.0));
+        assert!(layer.values().eq(expected.values()));
+    }
+}
+
+#[test]
+fn test_calculate_pixels_and_paths() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let kept_domain = domain::init(&area, ResolutionMultiplier::None);
+    let kept_machine = machine(&area, &kept_domain);
+    let kept_layers = kept_machine.init_layers();
+    let mut kept_stats = fractal_stats::init();
+    let kept = kept_machine.calculate_pixels_and_paths(&TEST, &kept_layers, PATHS_POINTS_MAX, &mut kept_stats);
+
+    // kept paths project to the same pixels
+    let projected = result_pixels::init(area.width_x, area.height_y);
+    projected.translate_paths_to_pixel_grid(&kept.paths, &area);
+    assert_eq!(kept.paths.len() as u64, kept_stats.new_elements_long);
+    assert!(kept_layers[0].values().eq(projected.values()));
+
+    // no points left, nothing is kept but pixels are complete
+    let dropped_domain = domain::init(&area, ResolutionMultiplier::None);
+    let dropped_machine = machine(&area, &dropped_domain);
+    let dropped_layers = dropped_machine.init_layers();
+    let mut dropped_stats = fractal_stats::init();
+    let dropped = dropped_machine.calculate_pixels_and_paths(&TEST, &dropped_layers, 0, &mut dropped_stats);
+
+    assert!(dropped.paths.is_empty());
+    assert_eq!(dropped_stats.new_elements_dropped, kept_stats.new_elements_long);
+    assert!(dropped_layers[0].values().eq(kept_layers[0].values()));
+}
+
+#[test]
+fn test_calculate_pixels_and_paths_recalculates_dropped() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let mut domain = domain::init(&area, ResolutionMultiplier::None);
+
+    // only few paths fit into points budget
+    let first_layers = machine(&area, &domain).init_layers();
+    let mut first_stats = fractal_stats::init();
+    let first = machine(&area, &domain).calculate_pixels_and_paths(&TEST, &first_layers, 1_000, &mut first_stats);
+    assert!(!first.paths.is_empty());
+    assert!(first_stats.new_elements_dropped > 0);
+
+    // next frame of the same view, kept paths are projected again and dropped elements are calculated again
+    domain.recalculate_pixels_positions_for_this_zoom(&area, true);
+    let second_machine = machine(&area, &domain);
+    let second_layers = second_machine.init_layers();
+    second_layers[0].translate_paths_to_pixel_grid(&first.paths, &area);
+    let mut second_stats = fractal_stats::init();
+    second_machine.calculate_pixels_and_paths(&TEST, &second_layers, 0, &mut second_stats);
+
+    assert_eq!(second_stats.new_elements_long, first_stats.new_elements_dropped);
+    assert!(second_layers[0].values().eq(first_layers[0].values()));
+}
+
+// the same picture calculated in f64 and in area rebased to double-double origin
+#[cfg(test)]
+fn assert_deep_zoom_same_as_f64(math: &fixture::Test) {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::machine;
+
+    let calculate = |area: &Area| {
+        let domain = domain::init(area, ResolutionMultiplier::None);
+        let machine = machine(area, &domain);
+        let layers = machine.init_layers();
+        let mut stats = fractal_stats::init();
+        machine.calculate_pixels(math, &layers, &mut stats);
+        (layers, stats)
+    };
+
+    let area_f64 = area::init(fixture::area_config());
+    let mut area_deep = area::init(fixture::area_config());
+    area_deep.rebase();
+    assert_eq!(area_deep.precision(), Precision::DoubleDouble);
+
+    let (pixels_f64, stats_f64) = calculate(&area_f64);
+    let (pixels_deep, stats_deep) = calculate(&area_deep);
+
+    // long orbits are chaotic, f64 and deep zoom points of few of them differ
+    assert!(stats_deep.new_elements_long > 0);
+    assert_eq!(stats_deep.new_elements_long, stats_f64.new_elements_long);
+    let total_f64: f64 = pixels_f64[0].values().sum();
+    let total_deep: f64 = pixels_deep[0].values().sum();
+    assert!((total_f64 - total_deep).abs() < total_f64 * 0.01);
+    let different = pixels_f64[0].values().zip(pixels_deep[0].values()).filter(|(a, b)| a != b).count();
+    assert!(different < 500);
+}
+
+#[test]
+fn test_calculate_pixels_double_double() {
+    assert_deep_zoom_same_as_f64(&fixture::Test { double_double: true, perturbation: false });
+}
+
+#[test]
+fn test_orbit_dd_new_mem_dd() {
+    use rusty_fractals_common::area;
+
+    // fractal with additional initial state, the orbit starts right of origin
+    struct Shifted {}
+    impl MathDD for Shifted {
+        fn math_dd(&self, m: &mut MemDD, origin_re: DoubleDouble, origin_im: DoubleDouble) {
+            m.re = origin_re;
+            m.im = origin_im;
+        }
+
+        fn new_mem_dd(&self, origin_re: DoubleDouble, origin_im: DoubleDouble) -> MemDD {
+            MemDD::new(origin_re + DoubleDouble::from(1.0), origin_im)
+        }
+    }
+
+    let area = area::init(fixture::area_config());
+    let mut orbit = OrbitDD::new(&area, 0.5, 0.25, &Shifted {});
+    assert_eq!(orbit.re_im(), (1.5, 0.25));
+    orbit.next();
+    assert_eq!(orbit.re_im(), (0.5, 0.25));
+
+    // initial state is preserved
+    orbit.reset();
+    assert_eq!(orbit.re_im(), (1.5, 0.25));
+}
+
+#[test]
+fn test_calculate_static_perturbation() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::machine;
+
+    // Misiurewicz point, far beyond f64 precision
+    let area = area::init(area::AreaConfig { width_re: 1e-12, center_re: -0.10109636384562, center_im: 0.95628651080914, width_x: 60, height_y: 60, rotation: 0.0 });
+    assert_eq!(area.precision(), Precision::DoubleDouble);
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = Machine {
+        calculation_config: CalculationConfig { fractal_type: FractalType::Mandelbrot, iteration_max: 5000, ..fixture::calculation_config() },
+        ..machine(&area, &domain)
+    };
+    let double_double = fixture::Test { double_double: true, perturbation: false };
+    let reference = perturbation::init(&area, machine.calculation_config.iteration_max);
+
+    let mut glitches = 0;
+    let mut different = 0;
+    for x in 0..area.width_x {
+        for y in 0..area.height_y {
+            let (re, im) = (area.screen_to_domain_re(x, y), area.screen_to_domain_im(x, y));
+            let (expected, _) = machine.calculate_escape(re, im, &double_double, Arithmetic::DoubleDouble(&double_double));
+            let mut orbit = reference.orbit(re, im);
+            let (iterator, _) = machine.calculate_escape_orbit(&mut orbit);
+            glitches += orbit.glitches();
+            if iterator != expected {
+                different += 1;
+                assert!(iterator.abs_diff(expected) <= 1, "{} iterations instead of {}", iterator, expected);
+            }
+        }
+    }
+
+    // rebased orbits are as precise as double-double orbits
+    assert!(glitches > 0);
+    assert!(different <= 5);
+}
+
+#[test]
+fn test_calculate_paths_perturbation_deep_zoom() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::machine;
+
+    // nucleus of period 3 component, orbits return to the area every 3rd iteration
+    // reference passes by 0 each time, and orbits glitch there
+    let area = area::init(area::AreaConfig { width_re: 1e-12, center_re: -0.1225611668766536, center_im: 0.7448617666197442, width_x: 20, height_y: 20, rotation: 0.0 });
+    assert_eq!(area.precision(), Precision::DoubleDouble);
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = Machine {
+        calculation_config: CalculationConfig { path_selection: PathSelection::NonDivergent, iteration_min: 3, iteration_max: 300, ..fixture::calculation_config() },
+        ..machine(&area, &domain)
+    };
+    let double_double = fixture::Test { double_double: true, perturbation: true };
+    let reference = perturbation::init(&area, machine.calculation_config.iteration_max);
+
+    let record = |arithmetic: Arithmetic| {
+        let mut result = result_data::init();
+        let points_left = AtomicUsize::new(PATHS_POINTS_MAX);
+        for el in domain.domain_elements.iter().flatten() {
+            machine.calculate_path_finite(el, &double_double, arithmetic, &mut Record::Paths { result: &mut result, points_left: &points_left }, &mut fractal_stats::init());
+        }
+        result
+    };
+    let expected = record(Arithmetic::DoubleDouble(&double_double));
+    let perturbed = record(Arithmetic::Perturbation(&reference));
+
+    let glitches: u32 = domain.domain_elements.iter().flatten().map(|el| {
+        let mut orbit = reference.orbit(el.origin_re, el.origin_im);
+        machine.calculate_escape_orbit(&mut orbit);
+        orbit.glitches()
+    }).sum();
+    assert!(glitches > 0);
+
+    // recorded points of rebased orbits are as precise as double-double points, far below pixel size
+    let tolerance = area.width_re / area.width_x as f64 * 1e-3;
+    assert!(!perturbed.paths.is_empty());
+    assert_eq!(perturbed.paths.len(), expected.paths.len());
+    for (path, expected_path) in perturbed.paths.iter().zip(&expected.paths) {
+        assert_eq!(path.len(), expected_path.len());
+        for (p, e) in path.iter().zip(expected_path) {
+            assert!((p[0] - e[0]).abs() < tolerance && (p[1] - e[1]).abs() < tolerance, "{:?} instead of {:?}", p, e);
+        }
+    }
+}
+
+#[test]
+fn test_reserve() {
+    let points_left = AtomicUsize::new(10);
+    assert!(reserve(&points_left, 6));
+    assert!(!reserve(&points_left, 6));
+    assert!(reserve(&points_left, 4));
+    assert_eq!(points_left.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn test_calculate_static() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(area::AreaConfig { width_re: 4.0, center_re: 0.0, center_im: 0.0, width_x: 40, height_y: 40, rotation: 0.0 });
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = Machine {
+        calculation_config: CalculationConfig { fractal_type: FractalType::Mandelbrot, iteration_min: 0, ..fixture::calculation_config() },
+        ..machine(&area, &domain)
+    };
+
+    let result_data = machine.calculate_static(&TEST);
+
+    // center [0, 0] is inside of Mandelbrot set, corner [-2, -2] diverges immediately
+    assert_eq!(result_data.el_at(20, 20).value(), 0);
+    assert_eq!(result_data.el_at(0, 0).value(), 1);
+    // [0.4, 0] is outside, it takes few iterations to diverge
+    assert!(result_data.el_at(24, 20).value() > 1);
+
+    let (result_image, _) = machine.calculate_frame(&TEST, &mut result_data::init(), &mut fractal_stats::init());
+    assert_eq!(result_image.get_pixel(20, 20), &image::Rgb([0, 0, 0]));
+}
+
+#[test]
+fn test_calculate_result_area_detail() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    // origins over the whole set, only points of small detail are recorded
+    let domain_area = area::init(fixture::area_config());
+    let result_area = area::init(area::AreaConfig { width_re: 0.5, center_re: -0.25, center_im: 0.5, width_x: 40, height_y: 20, rotation: 0.0 });
+    let domain = domain::init(&domain_area, ResolutionMultiplier::None);
+    let machine = Machine { result_area: &result_area, ..machine(&domain_area, &domain) };
+
+    let mut result_data = result_data::init();
+    let mut stats = fractal_stats::init();
+    let (result_image, domain_image) = machine.calculate_frame(&TEST, &mut result_data, &mut stats);
+
+    assert_eq!(result_image.dimensions(), (40, 20));
+    assert_eq!(domain_image.dimensions(), (100, 100));
+    assert!(!result_data.paths.is_empty());
+    for path in &result_data.paths {
+        assert!(path.iter().all(|p| result_area.contains(p[0], p[1])));
+    }
+    assert!(stats.pixels_value_total > 0);
+}
+
+#[test]
+#[should_panic(expected = "result area isn't inside of domain area")]
+fn test_calculate_result_area_outside() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let domain_area = area::init(fixture::area_config());
+    let result_area = area::init(area::AreaConfig { width_re: 0.5, center_re: 1.5, center_im: 0.0, width_x: 40, height_y: 20, rotation: 0.0 });
+    let domain = domain::init(&domain_area, ResolutionMultiplier::None);
+    let machine = Machine { result_area: &result_area, ..machine(&domain_area, &domain) };
+
+    machine.calculate_image(&TEST, &mut fractal_stats::init());
+}
+
+#[test]
+#[should_panic(expected = "Nebulabrot needs at least one band")]
+fn test_calculate_nebulabrot_without_bands() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::{machine, TEST};
+
+    let area = area::init(fixture::area_config());
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = Machine {
+        calculation_config: CalculationConfig { fractal_type: FractalType::Nebulabrot, ..fixture::calculation_config() },
+        ..machine(&area, &domain)
+    };
+
+    machine.calculate_frame(&TEST, &mut result_data::init(), &mut fractal_stats::init());
+}
+
+#[test]
+fn test_with_progress() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::machine;
+
+    let area = area::init(fixture::area_config());
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = machine(&area, &domain);
+    let layers = machine.init_layers();
+
+    let mut refreshed = Vec::new();
+    let result = machine.with_progress(
+        &layers,
+        Duration::from_millis(5),
+        || {
+            layers[0].add(10, 10);
+            thread::sleep(Duration::from_millis(100));
+            7
+        },
+        &mut |result_image, domain_image| refreshed.push((result_image.dimensions(), domain_image.dimensions())),
+    );
+
+    assert_eq!(result, 7);
+    assert!(!refreshed.is_empty());
+    assert!(refreshed.iter().all(|dimensions| *dimensions == ((100, 100), (100, 100))));
+}
+
+#[test]
+#[should_panic(expected = "calculation failed")]
+fn test_with_progress_panic() {
+    use rusty_fractals_common::area;
+    use rusty_fractals_domain::domain;
+    use crate::fixture::machine;
+
+    let area = area::init(fixture::area_config());
+    let domain = domain::init(&area, ResolutionMultiplier::None);
+    let machine = machine(&area, &domain);
+
+    machine.with_progress(&machine.init_layers(), Duration::from_millis(5), || panic!("calculation failed"), &mut |_, _| {});
+}
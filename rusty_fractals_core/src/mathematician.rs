@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use crate::fractal::MemType;
 use crate::mem::Mem;
 
 pub struct Mathematician {
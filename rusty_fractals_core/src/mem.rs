@@ -1,13 +1,32 @@
+use crate::fractal::MemType;
+
 // Memory object to carry calculation results
 pub struct Mem {
     pub re: f64,
     pub im: f64,
 }
 
-impl Mem {
-    pub(crate) fn new(re: f64, im: f64) -> Mem {
+impl MemType for Mem {
+    fn new(re: f64, im: f64) -> Mem {
         Mem { re, im }
     }
+
+    fn re(&self) -> f64 {
+        self.re
+    }
+
+    fn im(&self) -> f64 {
+        self.im
+    }
+
+    fn quad(&self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    fn reset(&mut self, re: f64, im: f64) {
+        self.re = re;
+        self.im = im;
+    }
 }
 
 impl Mem {
@@ -22,10 +41,6 @@ impl Mem {
         self.re = temp;
     }
 
-    pub fn quad(&self) -> f64 {
-        self.re * self.re + self.im * self.im
-    }
-
     pub fn conjugation(&mut self) {
         self.im = -1.0 * self.im;
     }
@@ -60,6 +75,17 @@ fn test_quad() {
     assert_eq!(q, 13.0);
 }
 
+#[test]
+fn test_reset() {
+    let mut m = Mem::new(3.0, 2.0);
+    m.square();
+
+    m.reset(3.0, 2.0);
+
+    assert_eq!(m.re(), 3.0);
+    assert_eq!(m.im(), 2.0);
+}
+
 #[test]
 fn test_conjugation() {
     let mut m = Mem { re: 3.0, im: 2.0 };
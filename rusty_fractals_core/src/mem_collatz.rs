@@ -1,19 +1,42 @@
+use crate::fractal::MemType;
 use crate::mem::Mem;
 
 pub struct MemCollatz {
     pub m: Mem,
     pub it: i32,
+    // Initial value of iteration counter, odd and even iterations differ
+    pub it_initial: i32,
 }
 
-impl MemCollatz {
-    pub fn re(&self) -> f64 {
+impl MemType for MemCollatz {
+    fn new(re: f64, im: f64) -> MemCollatz {
+        MemCollatz::init(re, im, 0)
+    }
+
+    fn re(&self) -> f64 {
         self.m.re
     }
 
-    pub fn im(&self) -> f64 {
+    fn im(&self) -> f64 {
         self.m.im
     }
 
+    fn quad(&self) -> f64 {
+        self.m.quad()
+    }
+
+    // initial iteration counter is fractal constant
+    fn reset(&mut self, re: f64, im: f64) {
+        self.m.reset(re, im);
+        self.it = self.it_initial;
+    }
+}
+
+impl MemCollatz {
+    pub fn init(re: f64, im: f64, it_initial: i32) -> MemCollatz {
+        MemCollatz { m: Mem::new(re, im), it: it_initial, it_initial }
+    }
+
     pub fn plus(&mut self, r: f64, i: f64) {
         self.m.plus(r, i);
     }
@@ -44,6 +67,7 @@ fn test_collatz_conjecture() {
     let mut c = MemCollatz {
         m: Mem { re: 0.0, im: 1.0 },
         it: 1,
+        it_initial: 1,
     };
 
     c.collatz_conjecture();
@@ -62,6 +86,7 @@ fn test_plus_collatz() {
     let mut c = MemCollatz {
         m: Mem { re: 0.0, im: 1.0 },
         it: 1,
+        it_initial: 1,
     };
 
     c.plus_collatz(0.0, 0.0);
@@ -69,3 +94,26 @@ fn test_plus_collatz() {
     assert_eq!(c.m.re, 0.5);
     assert_eq!(c.m.im, 1.5);
 }
+
+#[test]
+fn test_reset() {
+    let mut c = MemCollatz::new(0.0, 1.0);
+    c.collatz_conjecture();
+
+    c.reset(0.0, 1.0);
+
+    assert_eq!(c.re(), 0.0);
+    assert_eq!(c.im(), 1.0);
+    assert_eq!(c.it, 0);
+}
+
+#[test]
+fn test_reset_initial() {
+    let mut c = MemCollatz::init(0.0, 1.0, 1);
+    c.collatz_conjecture();
+    assert_eq!(c.it, 2);
+
+    c.reset(0.0, 1.0);
+
+    assert_eq!(c.it, 1);
+}
@@ -0,0 +1,55 @@
+use rusty_fractals_common::double_double::DoubleDouble;
+
+// Memory object to carry calculation results in double-double precision, for deep zoom
+// Coordinates are absolute, not relative to area origin
+pub struct MemDD {
+    pub re: DoubleDouble,
+    pub im: DoubleDouble,
+}
+
+impl MemDD {
+    pub fn new(re: DoubleDouble, im: DoubleDouble) -> MemDD {
+        MemDD { re, im }
+    }
+
+    // precision of f64 is enough to decide divergence
+    pub fn quad(&self) -> f64 {
+        self.re.hi * self.re.hi + self.im.hi * self.im.hi
+    }
+
+    pub fn plus(&mut self, r: DoubleDouble, i: DoubleDouble) {
+        self.re = self.re + r;
+        self.im = self.im + i;
+    }
+
+    pub fn square(&mut self) {
+        let temp = self.re.square() - self.im.square();
+        self.im = self.re * self.im * 2.0;
+        self.re = temp;
+    }
+
+    pub fn conjugation(&mut self) {
+        self.im = -self.im;
+    }
+}
+
+#[test]
+fn test_square() {
+    let mut m = MemDD::new(DoubleDouble::from(3.0), DoubleDouble::from(2.0));
+
+    m.square();
+
+    assert_eq!(m.re.to_f64(), 5.0);
+    assert_eq!(m.im.to_f64(), 12.0);
+    assert_eq!(m.quad(), 169.0);
+}
+
+#[test]
+fn test_plus() {
+    let mut m = MemDD::new(DoubleDouble::from(1.0), DoubleDouble::from(0.0));
+
+    m.plus(DoubleDouble::from(1e-20), DoubleDouble::from(-1.0));
+
+    assert_eq!((m.re - DoubleDouble::from(1.0)).to_f64(), 1e-20);
+    assert_eq!(m.im.to_f64(), -1.0);
+}
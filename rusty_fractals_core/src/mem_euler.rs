@@ -0,0 +1,77 @@
+use crate::fractal::MemType;
+use crate::mathematician::Mathematician;
+use crate::mem::Mem;
+
+/* For simplicity Euler Fractal uses only three explicitly defined spectra, each is one layer of result pixels */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Spectra { Red, Green, Blue }
+
+/**
+ * Memory object for Euler fractal
+ */
+pub struct MemEuler {
+    pub m: Mem,
+    pub it: u32,
+}
+
+impl MemType for MemEuler {
+    fn new(re: f64, im: f64) -> MemEuler {
+        MemEuler { m: Mem::new(re, im), it: 0 }
+    }
+
+    fn re(&self) -> f64 {
+        self.m.re
+    }
+
+    fn im(&self) -> f64 {
+        self.m.im
+    }
+
+    fn quad(&self) -> f64 {
+        self.m.quad()
+    }
+
+    fn reset(&mut self, re: f64, im: f64) {
+        self.m.reset(re, im);
+        self.it = 0;
+    }
+}
+
+impl MemEuler {
+    pub fn plus(&mut self, r: f64, i: f64) {
+        self.m.plus(r, i);
+    }
+
+    pub fn square(&mut self) {
+        self.m.square();
+    }
+
+    // Mathematician is shared by fractal math, too expensive to create for each calculation
+    pub fn euler(&mut self, math: &Mathematician) {
+        self.it += 1;
+        if math.is_prime(&self.it) {
+            self.m.re = 0.01 / self.m.re;
+            self.m.im = 0.01 / self.m.im;
+        }
+    }
+}
+
+#[test]
+fn test_euler() {
+    use std::collections::HashSet;
+
+    let math = Mathematician {
+        primes: HashSet::from([2, 3]),
+        fibonacci: HashSet::new(),
+        perfect: HashSet::new(),
+        square: HashSet::new(),
+    };
+    let mut me = MemEuler::new(0.5, 0.25);
+
+    me.euler(&math);
+    assert_eq!(me.re(), 0.5);
+
+    me.euler(&math);
+    assert_eq!(me.re(), 0.02);
+    assert_eq!(me.im(), 0.04);
+}
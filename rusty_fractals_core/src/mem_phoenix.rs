@@ -1,3 +1,4 @@
+use crate::fractal::MemType;
 use crate::mem::Mem;
 
 /**
@@ -7,6 +8,8 @@ pub struct MemPhoenix {
     pub m: Mem,
     pub c: f64,
     pub p: f64,
+    // Initial value of previous calculation results
+    pub phoenix_initializer: f64,
     // Values of previous calculation results
     pub prev_prev_re: f64,
     pub prev_prev_im: f64,
@@ -14,15 +17,47 @@ pub struct MemPhoenix {
     pub prev_im: f64,
 }
 
-impl MemPhoenix {
-    pub fn re(&self) -> f64 {
+impl MemType for MemPhoenix {
+    fn new(re: f64, im: f64) -> MemPhoenix {
+        MemPhoenix::init(re, im, 0.0, 0.0, 0.0)
+    }
+
+    fn re(&self) -> f64 {
         self.m.re
     }
 
-    pub fn im(&self) -> f64 {
+    fn im(&self) -> f64 {
         self.m.im
     }
 
+    fn quad(&self) -> f64 {
+        self.m.quad()
+    }
+
+    // c, p and initializer are fractal constants, only calculation results are reset
+    fn reset(&mut self, re: f64, im: f64) {
+        self.m.reset(re, im);
+        self.prev_prev_re = self.phoenix_initializer;
+        self.prev_prev_im = self.phoenix_initializer;
+        self.prev_re = self.phoenix_initializer;
+        self.prev_im = self.phoenix_initializer;
+    }
+}
+
+impl MemPhoenix {
+    pub fn init(re: f64, im: f64, c: f64, p: f64, phoenix_initializer: f64) -> MemPhoenix {
+        MemPhoenix {
+            m: Mem::new(re, im),
+            c,
+            p,
+            phoenix_initializer,
+            prev_prev_re: phoenix_initializer,
+            prev_prev_im: phoenix_initializer,
+            prev_re: phoenix_initializer,
+            prev_im: phoenix_initializer,
+        }
+    }
+
     pub fn plus(&mut self, r: f64, i: f64) {
         self.m.plus(r, i);
     }
@@ -31,3 +66,20 @@ impl MemPhoenix {
         self.m.square();
     }
 }
+
+#[test]
+fn test_reset() {
+    let mut mp = MemPhoenix::init(0.5, 0.2, 0.35, -0.25, 1.0);
+    mp.prev_re = 0.1;
+    mp.prev_prev_im = 0.3;
+    mp.square();
+
+    mp.reset(0.5, 0.2);
+
+    assert_eq!(mp.re(), 0.5);
+    assert_eq!(mp.im(), 0.2);
+    assert_eq!(mp.c, 0.35);
+    assert_eq!(mp.p, -0.25);
+    assert_eq!(mp.prev_re, 1.0);
+    assert_eq!(mp.prev_prev_im, 1.0);
+}
@@ -0,0 +1,193 @@
+// Perturbation of z² + c, for deep zoom
+// Reference orbit Z is calculated once in double-double precision, from the center of area
+// Orbit of each domain element is z = Z + δ, only small δ is calculated in f64
+// δ' = 2Zδ + δ² + δc
+// When |z| < |δ|, δ lost its precision, glitch. Orbit is rebased to the start of reference orbit, Z[0] = 0
+// Rebased z is kept in double-double, so that δ of the next iteration is again small and relative to Z[1] = C
+// https://fractalforums.org/f/28/t/4360
+
+use rusty_fractals_common::area::Area;
+use rusty_fractals_common::constants::CALCULATION_BOUNDARY;
+use rusty_fractals_common::double_double::DoubleDouble;
+
+pub struct ReferenceOrbit {
+    // absolute coordinates, Z[0] = 0, Z[1] = C
+    z: Vec<[f64; 2]>,
+    // the same points in double-double, rebased orbits take z from these
+    z_dd: Vec<[DoubleDouble; 2]>,
+    // the same points relative to area origin
+    z_relative: Vec<[f64; 2]>,
+    // absolute area origin
+    origin_re: DoubleDouble,
+    origin_im: DoubleDouble,
+    // reference origin C, relative to area origin
+    c_re: f64,
+    c_im: f64,
+}
+
+// Orbit of single origin c = C + δc
+pub struct PerturbedOrbit<'a> {
+    reference: &'a ReferenceOrbit,
+    dc_re: f64,
+    dc_im: f64,
+    // z = Z[n] + δ
+    n: usize,
+    d_re: f64,
+    d_im: f64,
+    // absolute z of rebased orbit, used instead of δ while n is 0
+    z_re: DoubleDouble,
+    z_im: DoubleDouble,
+    // how many times was orbit rebased because of glitch
+    glitches: u32,
+}
+
+impl ReferenceOrbit {
+    fn len(&self) -> usize {
+        self.z.len()
+    }
+
+    // origin relative to area origin
+    pub fn orbit(&self, re: f64, im: f64) -> PerturbedOrbit<'_> {
+        let (dc_re, dc_im) = (re - self.c_re, im - self.c_im);
+        PerturbedOrbit { reference: self, dc_re, dc_im, n: 1, d_re: dc_re, d_im: dc_im, z_re: DoubleDouble::ZERO, z_im: DoubleDouble::ZERO, glitches: 0 }
+    }
+}
+
+impl PerturbedOrbit<'_> {
+    pub fn next(&mut self) {
+        if self.n + 1 >= self.reference.len() {
+            // reference orbit diverged already, or it is too short
+            self.rebase();
+        }
+        if self.n == 0 {
+            // z² + c = C + z² + δc
+            self.d_re = (self.z_re.square() - self.z_im.square() + self.dc_re).to_f64();
+            self.d_im = (self.z_re * self.z_im * 2.0 + self.dc_im).to_f64();
+        } else {
+            let [zr, zi] = self.reference.z[self.n];
+            let (dr, di) = (self.d_re, self.d_im);
+            self.d_re = 2.0 * (zr * dr - zi * di) + (dr * dr - di * di) + self.dc_re;
+            self.d_im = 2.0 * (zr * di + zi * dr) + 2.0 * dr * di + self.dc_im;
+        }
+        self.n += 1;
+
+        if self.quad() < self.d_re * self.d_re + self.d_im * self.d_im {
+            // glitch
+            self.rebase();
+            self.glitches += 1;
+        }
+    }
+
+    pub fn glitches(&self) -> u32 {
+        self.glitches
+    }
+
+    // Z[0] is 0, z = Z[n] + δ is computed in double-double, not to lose its precision to Z[n]
+    fn rebase(&mut self) {
+        let [zr, zi] = self.reference.z_dd[self.n];
+        self.z_re = zr + self.d_re;
+        self.z_im = zi + self.d_im;
+        self.n = 0;
+    }
+
+    pub fn quad(&self) -> f64 {
+        if self.n == 0 {
+            return self.z_re.hi * self.z_re.hi + self.z_im.hi * self.z_im.hi;
+        }
+        let [zr, zi] = self.reference.z[self.n];
+        let (re, im) = (zr + self.d_re, zi + self.d_im);
+        re * re + im * im
+    }
+
+    // relative to area origin
+    pub fn re_im(&self) -> (f64, f64) {
+        if self.n == 0 {
+            return ((self.z_re - self.reference.origin_re).to_f64(), (self.z_im - self.reference.origin_im).to_f64());
+        }
+        let [zr, zi] = self.reference.z_relative[self.n];
+        (zr + self.d_re, zi + self.d_im)
+    }
+
+    // start again from origin, z = c
+    pub fn reset(&mut self) {
+        self.n = 1;
+        self.d_re = self.dc_re;
+        self.d_im = self.dc_im;
+    }
+}
+
+// Reference orbit from center of area, until it diverges or up to iteration_max
+pub fn init(area: &Area, iteration_max: u32) -> ReferenceOrbit {
+    println!("reference orbit");
+    let (c_re, c_im) = area.to_absolute(area.center_re, area.center_im);
+    let cb = CALCULATION_BOUNDARY as f64;
+    let mut z = vec![[0.0, 0.0]];
+    let mut z_dd = vec![[DoubleDouble::ZERO, DoubleDouble::ZERO]];
+    let mut z_relative = vec![{
+        let (re, im) = area.to_relative(DoubleDouble::ZERO, DoubleDouble::ZERO);
+        [re, im]
+    }];
+    let (mut re, mut im) = (c_re, c_im);
+    for _ in 0..=iteration_max {
+        z.push([re.to_f64(), im.to_f64()]);
+        z_dd.push([re, im]);
+        let (rel_re, rel_im) = area.to_relative(re, im);
+        z_relative.push([rel_re, rel_im]);
+        if re.hi * re.hi + im.hi * im.hi >= cb {
+            break;
+        }
+        let temp = re.square() - im.square() + c_re;
+        im = re * im * 2.0 + c_im;
+        re = temp;
+    }
+    ReferenceOrbit { z, z_dd, z_relative, origin_re: area.origin_re, origin_im: area.origin_im, c_re: area.center_re, c_im: area.center_im }
+}
+
+#[cfg(test)]
+fn test_area() -> Area {
+    use rusty_fractals_common::area;
+    area::init(area::AreaConfig { width_re: 0.1, center_re: -0.5, center_im: 0.1, width_x: 10, height_y: 10, rotation: 0.0 })
+}
+
+#[test]
+fn test_reference_orbit() {
+    let area = test_area();
+    let reference = init(&area, 100);
+    // [-0.5, 0.1] is inside of Mandelbrot set
+    assert_eq!(reference.len(), 102);
+    assert_eq!(reference.z[1], [-0.5, 0.1]);
+    assert_eq!(reference.z_relative[1], [-0.5, 0.1]);
+}
+
+#[test]
+fn test_perturbed_orbit() {
+    let area = test_area();
+    let reference = init(&area, 100);
+    let (c_re, c_im) = (-0.49, 0.11);
+    let mut orbit = reference.orbit(c_re, c_im);
+    let (mut re, mut im) = (c_re, c_im);
+    for _ in 0..50 {
+        assert!((orbit.re_im().0 - re).abs() < 1e-9);
+        assert!((orbit.re_im().1 - im).abs() < 1e-9);
+        orbit.next();
+        let temp = re * re - im * im + c_re;
+        im = 2.0 * re * im + c_im;
+        re = temp;
+    }
+    orbit.reset();
+    assert_eq!(orbit.re_im(), (c_re, c_im));
+}
+
+#[test]
+fn test_perturbed_orbit_rebased() {
+    // reference diverges after few iterations, orbit of [0, 0] doesn't
+    use rusty_fractals_common::area;
+    let area = area::init(area::AreaConfig { width_re: 0.1, center_re: 0.5, center_im: 0.0, width_x: 10, height_y: 10, rotation: 0.0 });
+    let reference = init(&area, 100);
+    assert!(reference.len() < 10);
+    let mut orbit = reference.orbit(0.0, 0.0);
+    for _ in 0..100 {
+        orbit.next();
+        assert!(orbit.quad() < 1e-20);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+use image::{imageops, RgbImage};
+#[cfg(feature = "window")]
+use minifb::{MouseButton, MouseMode, Window, WindowOptions};
+use rusty_fractals_common::target;
+use rusty_fractals_common::target::Target;
+
+// Displays frames and provides mouse clicks
+pub trait ViewerBackend {
+    fn show(&mut self, image: &RgbImage);
+    // mouse clicks since the last call, in image pixel coordinates from the top left corner
+    fn clicks(&mut self) -> Vec<(usize, usize)>;
+    fn is_open(&self) -> bool;
+}
+
+// Shows result image and domain mask side by side, click to either of them sets zoom target
+pub struct Viewer {
+    backend: Box<dyn ViewerBackend>,
+    pub target: Target,
+    // last shown image, result image on the left, domain image on the right
+    pub image: RgbImage,
+}
+
+impl Viewer {
+    pub fn repaint(&mut self, result_image: &RgbImage, domain_image: &RgbImage) {
+        imageops::replace(&mut self.image, result_image, 0, 0);
+        imageops::replace(&mut self.image, domain_image, self.target.width as i64, 0);
+        self.backend.show(&self.image);
+    }
+
+    // last click sets new zoom target
+    pub fn update_target(&mut self) -> bool {
+        match self.backend.clicks().last() {
+            Some(&(x, y)) if y < self.target.height => {
+                // both halves show the same area
+                self.target.update(x % self.target.width, y);
+                println!("target: {}, {}", self.target.screen_from_center_x(), self.target.screen_from_center_y());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.backend.is_open()
+    }
+}
+
+pub fn init(backend: Box<dyn ViewerBackend>, width_x: usize, height_y: usize) -> Viewer {
+    Viewer {
+        backend,
+        target: target::init(width_x, height_y),
+        image: RgbImage::new(2 * width_x as u32, height_y as u32),
+    }
+}
+
+// Window if there is a display, headless otherwise
+#[cfg(feature = "window")]
+pub fn init_window(name: &str, width_x: usize, height_y: usize) -> Viewer {
+    let backend: Box<dyn ViewerBackend> = match WindowBackend::new(name, 2 * width_x, height_y) {
+        Ok(window) => Box::new(window),
+        Err(e) => {
+            println!("No window, continue without viewer: {}", e);
+            Box::new(HeadlessBackend::new(Vec::new()))
+        }
+    };
+    init(backend, width_x, height_y)
+}
+
+// built without window feature
+#[cfg(not(feature = "window"))]
+pub fn init_window(_name: &str, width_x: usize, height_y: usize) -> Viewer {
+    println!("Built without window, continue without viewer");
+    init_headless(width_x, height_y)
+}
+
+#[cfg(feature = "window")]
+pub struct WindowBackend {
+    window: Window,
+    buffer: Vec<u32>,
+    mouse_was_down: bool,
+}
+
+#[cfg(feature = "window")]
+impl WindowBackend {
+    pub fn new(name: &str, width: usize, height: usize) -> Result<WindowBackend, minifb::Error> {
+        let window = Window::new(name, width, height, WindowOptions::default())?;
+        Ok(WindowBackend { window, buffer: vec![0; width * height], mouse_was_down: false })
+    }
+}
+
+#[cfg(feature = "window")]
+impl ViewerBackend for WindowBackend {
+    fn show(&mut self, image: &RgbImage) {
+        for (i, image::Rgb([r, g, b])) in image.pixels().enumerate() {
+            self.buffer[i] = (*r as u32) << 16 | (*g as u32) << 8 | *b as u32;
+        }
+        if let Err(e) = self.window.update_with_buffer(&self.buffer, image.width() as usize, image.height() as usize) {
+            println!("repaint failed: {}", e);
+        }
+    }
+
+    // window events are processed only here and at repaint, hold the mouse button until the next frame
+    fn clicks(&mut self) -> Vec<(usize, usize)> {
+        self.window.update();
+        let mouse_down = self.window.get_mouse_down(MouseButton::Left);
+        let mut clicks = Vec::new();
+        if mouse_down && !self.mouse_was_down {
+            if let Some((x, y)) = self.window.get_mouse_pos(MouseMode::Discard) {
+                clicks.push((x as usize, y as usize));
+            }
+        }
+        self.mouse_was_down = mouse_down;
+        clicks
+    }
+
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+}
+
+// In memory backend for machines without display. Clicks are scripted, one click for each call.
+pub struct HeadlessBackend {
+    clicks: VecDeque<(usize, usize)>,
+    pub frames_shown: u32,
+}
+
+impl HeadlessBackend {
+    pub fn new(clicks: Vec<(usize, usize)>) -> HeadlessBackend {
+        HeadlessBackend { clicks: VecDeque::from(clicks), frames_shown: 0 }
+    }
+}
+
+impl ViewerBackend for HeadlessBackend {
+    fn show(&mut self, _image: &RgbImage) {
+        self.frames_shown += 1;
+    }
+
+    fn clicks(&mut self) -> Vec<(usize, usize)> {
+        self.clicks.pop_front().into_iter().collect()
+    }
+
+    fn is_open(&self) -> bool {
+        true
+    }
+}
+
+pub fn init_headless(width_x: usize, height_y: usize) -> Viewer {
+    init(Box::new(HeadlessBackend::new(Vec::new())), width_x, height_y)
+}
+
+#[test]
+fn test_repaint() {
+    use image::Rgb;
+
+    let mut viewer = init_headless(4, 3);
+    let result_image = RgbImage::from_pixel(4, 3, Rgb([255, 255, 255]));
+    let domain_image = RgbImage::from_pixel(4, 3, Rgb([0, 0, 255]));
+
+    viewer.repaint(&result_image, &domain_image);
+
+    assert_eq!(viewer.image.dimensions(), (8, 3));
+    assert_eq!(viewer.image.get_pixel(3, 2), &Rgb([255, 255, 255]));
+    assert_eq!(viewer.image.get_pixel(4, 0), &Rgb([0, 0, 255]));
+}
+
+#[test]
+fn test_update_target() {
+    let mut viewer = init(Box::new(HeadlessBackend::new(vec![(1, 2), (6, 0), (3, 5)])), 4, 3);
+    assert_eq!(viewer.target.screen_from_corner_x(), 2);
+
+    assert!(viewer.update_target());
+    assert_eq!(viewer.target.screen_from_corner_x(), 1);
+    assert_eq!(viewer.target.screen_from_corner_y(), 2);
+
+    // click to domain image
+    assert!(viewer.update_target());
+    assert_eq!(viewer.target.screen_from_corner_x(), 2);
+    assert_eq!(viewer.target.screen_from_corner_y(), 0);
+
+    // outside of images
+    assert!(!viewer.update_target());
+    // no more clicks
+    assert!(!viewer.update_target());
+    assert_eq!(viewer.target.screen_from_corner_x(), 2);
+}
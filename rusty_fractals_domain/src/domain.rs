@@ -3,31 +3,43 @@ use image::{Rgb, RgbImage};
 use crate::{domain_element, resolution_multiplier};
 use domain_element::DomainElement;
 use crate::resolution_multiplier::ResolutionMultiplier;
+use crate::resolution_multiplier::ResolutionMultiplier::SquareAlter;
 
 use rand::thread_rng;
 use rand::seq::SliceRandom;
 use rusty_fractals_common::area::Area;
-use rusty_fractals_common::constants::NEIGHBOURS;
+use rusty_fractals_common::constants::{CHUNKS, NEIGHBOURS};
 use crate::domain_element::{active_new, hibernated_deep_black};
 use crate::pixel_states::{ACTIVE_NEW, DomainElementState, FINISHED, FINISHED_SUCCESS, FINISHED_SUCCESS_PAST, FINISHED_TOO_LONG, FINISHED_TOO_SHORT, GOOD_PATH, HIBERNATED_DEEP_BLACK};
 
-pub struct Domain<'lif> {
+pub struct Domain {
     pub width: usize,
     pub height: usize,
-    pub domain_area: &'lif Area,
     pub domain_elements: Vec<Vec<DomainElement>>,
     pub resolution_multiplier: resolution_multiplier::ResolutionMultiplier,
+    // SquareAlter uses different pixel corners for odd and even zoom iterations
+    pub odd: bool,
 }
 
-impl Domain<'_> {
+// How were domain elements placed to zoomed in area
+// moved: kept from previous calculation
+// hibernated: new elements surrounded by Mandelbrot interior, these won't be calculated
+// created: new elements to calculate
+pub struct ZoomCounts {
+    pub moved: u64,
+    pub hibernated: u64,
+    pub created: u64,
+}
+
+impl Domain {
     fn check_domain(&self, x: i32, y: i32) -> bool {
         x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32
     }
 
     pub fn shuffled_calculation_coordinates(&self) -> Vec<[u32; 2]> {
         let mut coordinates_xy: Vec<[u32; 2]> = Vec::new();
-        for x in 0..19 {
-            for y in 0..19 {
+        for x in 0..CHUNKS {
+            for y in 0..CHUNKS {
                 coordinates_xy.push([x, y]);
             }
         }
@@ -35,35 +47,41 @@ impl Domain<'_> {
         coordinates_xy
     }
 
-    // Don't do any wrapping the first time because Mandelbrot elements are not optimized.
-    /*
-    fn wrap(&self, rm: ResolutionMultiplier, odd: bool) {
-        if rm == SquareAlter {
-            let d = self.domain_area.plank() / 3;
-            if odd {
-                domainFull.add(activeNew(elementZero.originRe + d, elementZero.originIm + d));
-                domainFull.add(activeNew(elementZero.originRe - d, elementZero.originIm - d));
+    // Fill pixel of domain element with more calculation origins
+    // The element is the center of the pixel, it remains the only element with relevant state
+    // Offsets are along the pixel grid, which is rotated with the area
+    pub fn wrap(&self, el: &DomainElement, area: &Area) -> Vec<DomainElement> {
+        let mut wrapped: Vec<DomainElement> = Vec::new();
+        let origin = |u: f64, v: f64| {
+            let (re, im) = area.rotate_offset(u, v);
+            active_new(el.origin_re + re, el.origin_im + im)
+        };
+        if self.resolution_multiplier == SquareAlter {
+            let d = area.plank() / 3.0;
+            if self.odd {
+                wrapped.push(origin(d, d));
+                wrapped.push(origin(-d, -d));
             } else {
-                domainFull.add(activeNew(elementZero.originRe - d, elementZero.originIm + d));
-                domainFull.add(activeNew(elementZero.originRe + d, elementZero.originIm - d));
+                wrapped.push(origin(-d, d));
+                wrapped.push(origin(d, -d));
             }
         } else {
-            let multiplier = self.resolve_multiplier(rm);
+            let multiplier = Domain::resolve_multiplier(self.resolution_multiplier);
 
-            let d = self.domain_area.plank() / multiplier;
-            let half = (multiplier - 1) / 2;
+            let d = area.plank() / multiplier;
+            let half = ((multiplier - 1.0) / 2.0) as i32;
             // This fills the pixel with multiple points
-            for x in -half..half {
-                for y in -half..half {
+            for x in -half..=half {
+                for y in -half..=half {
                     if x != 0 || y != 0 {
-                        domainFull.add(activeNew(elementZero.originRe + (x * d), elementZero.originIm + (y * d)));
+                        wrapped.push(origin(x as f64 * d, y as f64 * d));
                     }
-                    // else do nothing, there already is element0 for the center of this pixel
+                    // else do nothing, there already is element for the center of this pixel
                 }
             }
         }
+        wrapped
     }
-    */
 
     fn resolve_multiplier(rm: ResolutionMultiplier) -> f64 {
         match rm {
@@ -79,7 +97,7 @@ impl Domain<'_> {
 
     // Colors for Mandelbrot image based on Mandelbrot element's state
     pub fn color_for_state(el: &DomainElement) -> Rgb<u8> {
-        match el.state {
+        match el.state() {
             // most of the elements are going to be FinishedSuccessPast
             DomainElementState::FinishedSuccessPast => FINISHED_SUCCESS_PAST,
             DomainElementState::HibernatedDeepBlack => HIBERNATED_DEEP_BLACK,
@@ -94,108 +112,148 @@ impl Domain<'_> {
 
     pub fn domain_element_states_to_image(&self) -> RgbImage {
         let mut domain_image = RgbImage::new(self.width as u32, self.height as u32);
-        for y in 0..self.height - 1 {
-            for x in 0..self.width - 1 {
+        for y in 0..self.height {
+            for x in 0..self.width {
                 domain_image.put_pixel(x as u32, y as u32, Domain::color_for_state(&self.domain_elements[x][y]));
             }
         }
         domain_image
     }
 
+    // area origin moved by [re, im], keep origins of elements at the same place
+    pub fn shift(&mut self, re: f64, im: f64) {
+        for el in self.domain_elements.iter_mut().flatten() {
+            el.origin_re -= re;
+            el.origin_im -= im;
+        }
+    }
+
     // This is called after calculation finished, zoom was called and new area measures recalculated
-    /*
-    pub fn recalculate_pixels_positions_for_this_zoom(&mut self) {
+    // hibernate: Mandelbrot interior isn't recorded, new elements surrounded by it won't be calculated
+    pub fn recalculate_pixels_positions_for_this_zoom(&mut self, area: &Area, hibernate: bool) -> ZoomCounts {
+        println!("recalculate_pixels_positions_for_this_zoom()");
+        self.odd = !self.odd;
         // Scan domain elements : old positions from previous calculation
         // Some elements will be moved to new positions
         // For all the moved elements, all the next calculations will be skipped.
 
-        let mut elements_to_move = Vec::new();
-
-        let width = self.domain_area.width_x;
-        let height = self.domain_area.height_y;
-
-        for y in 0..height {
-            for x in 0..width {
-                let el = self.domain_elements[xx][yy];
+        let mut elements_to_move: Vec<DomainElement> = Vec::new();
+        for column in self.domain_elements.drain(..) {
+            for el in column {
                 // There was already zoom in, the new area is smaller
-                if self.domain_area.contains(el.origin_re, el.origin_im) {
+                if area.contains(el.origin_re, el.origin_im) {
                     // Element did not move out of the zoomed in area
                     elements_to_move.push(el);
                 }
             }
         }
 
-
-        // If there is a conflict, two or more points moved to same pixel, then use the active one if there is any.
+        // If there is a conflict, two or more points moved to same pixel, then use the one with the best state, by order of DomainElementState.
+        // Finished elements win over ActiveNew ones, their paths were already calculated.
         // Don't drop conflicts around, simply calculate new elements in the next calculation iteration. Because that would create really bad mess.
 
+        let mut moved: Vec<Vec<Option<DomainElement>>> = Vec::new();
+        for _ in 0..self.width {
+            let mut column = Vec::new();
+            for _ in 0..self.height {
+                column.push(None);
+            }
+            moved.push(column);
+        }
+
+        let mut moved_count = 0;
         for el in elements_to_move {
-            // translate [px,py] to [re,im]
-            (px, py) = self.domain_area.domain_point_to_result_pixel(el.origin_re, el.origin_im);
-
-            let filled_already = self.domain_elements[px][py];
-            if filled_already != null {
-                // conflict
-                if filled_already.has_worse_state_then(el) {
-                    // Replace by element with better state
-                    // Better to delete the other one, then to drop it to other empty pixel.
-                    // That would cause problem with optimization, better calculate new and shiny pixel
-                    self.domain_elements[px][py] = el;
+            // translate [re,im] to [px,py]
+            let (px, py) = area.domain_point_to_result_pixel(el.origin_re, el.origin_im);
+            if px >= self.width || py >= self.height {
+                // rounded out of the domain at the very border
+                continue;
+            }
+            match &moved[px][py] {
+                Some(filled_already) => {
+                    // conflict
+                    if filled_already.has_worse_state_then(&el) {
+                        // Replace by element with better state
+                        // Better to delete the other one, then to drop it to other empty pixel.
+                        // That would cause problem with optimization, better calculate new and shiny pixel
+                        moved[px][py] = Some(el);
+                    }
+                }
+                None => {
+                    // Good, there is no conflict
+                    moved[px][py] = Some(el);
+                    moved_count += 1;
                 }
-            } else {
-                // Good, there is no conflict
-                self.domain_elements[m.px][m.py] = el;
             }
         }
+        println!("moved:          {}", moved_count);
 
-        // Repaint with only moved elements
-        maskFullUpdate();
-        Application.repaintMaskMandelbrotWindow();
+        // Calculation for some positions should be skipped as they are too far away form any long successful divergent position
+        let mut hibernated: Vec<Vec<bool>> = Vec::new();
+        for x in 0..self.width {
+            let mut column = Vec::new();
+            for y in 0..self.height {
+                column.push(hibernate && moved[x][y].is_none() && self.all_neighbors_finished_too_long(&moved, x, y));
+            }
+            hibernated.push(column);
+        }
 
         // Create new elements on positions where nothing was moved to
-        for y in 0..height {
-            for x in 0..width {
-                let el = self.domain_elements[x][y];
-                if el == null {
-                    self.domain_area.screenToDomainCarry(m, x, y);
-                    if allNeighborsFinishedTooLong(x, y) {
-                        // Calculation for some positions should be skipped as they are too far away form any long successful divergent position
-                        self.domain_elements[x][y] = hibernated_deep_black(m.re, m.im);
-                    } else {
-                        self.domain_elements[x][y] = active_new(m.re, m.im);
+        let mut created_count = 0;
+        let mut hibernated_count = 0;
+        for (x, column) in moved.into_iter().enumerate() {
+            let mut vx: Vec<DomainElement> = Vec::new();
+            for (y, el) in column.into_iter().enumerate() {
+                match el {
+                    Some(el) => {
+                        // If relevant, mark it as element from previous calculation iteration
+                        el.past();
+                        vx.push(el);
+                    }
+                    None => {
+                        let re = area.screen_to_domain_re(x, y);
+                        let im = area.screen_to_domain_im(x, y);
+                        if hibernated[x][y] {
+                            vx.push(hibernated_deep_black(re, im));
+                            hibernated_count += 1;
+                        } else {
+                            vx.push(active_new(re, im));
+                            created_count += 1;
+                        }
                     }
-                } else {
-                    // If relevant, mark it as element from previous calculation iteration
-                    el.past();
                 }
             }
+            self.domain_elements.push(vx);
         }
-
-        elements_to_move.clear();
+        println!("hibernated:     {}", hibernated_count);
+        println!("created new:    {}", created_count);
+        ZoomCounts { moved: moved_count, hibernated: hibernated_count, created: created_count }
     }
-    */
 
     // Verify if any neighbor px,py finished well, long or at least too short.
     // This method identifies deep black convergent elements of Mandelbrot set interior.
     // Don't do any calculation for those.
-    /*
-    fn all_neighbors_finished_too_long(&mut self, x: u32, y: u32) -> bool {
+    // Empty positions are unknown, at least one neighbor must be known to be too long.
+    fn all_neighbors_finished_too_long(&self, moved: &[Vec<Option<DomainElement>>], x: usize, y: usize) -> bool {
         let neigh = NEIGHBOURS as i32;
-        for a in -neigh..neigh {
-            for b in -neigh..neigh {
+        let mut too_long = false;
+        for a in -neigh..=neigh {
+            for b in -neigh..=neigh {
                 let xx = x as i32 + a;
                 let yy = y as i32 + b;
                 if self.check_domain(xx, yy) {
-                    let el = self.domain_elements[xx as usize][yy as usize];
-                    if el.is_finished_success_any() || el.is_finished_too_short() {
-                        return false;
+                    if let Some(el) = &moved[xx as usize][yy as usize] {
+                        if el.is_finished_too_long() || el.is_hibernated_deep_black() {
+                            too_long = true;
+                        } else if !el.is_active_new() {
+                            return false;
+                        }
                     }
                 }
             }
         }
-        true
+        too_long
     }
-    */
 
     // All new elements are Active New
     // For wrapping, search only elements, which have some past well finished neighbors
@@ -235,17 +293,176 @@ impl Domain<'_> {
     }
 }
 
+pub fn init(domain_area: &Area, resolution_multiplier: ResolutionMultiplier) -> Domain {
+    Domain {
+        width: domain_area.width_x,
+        height: domain_area.height_y,
+        domain_elements: init_domain_elements(domain_area),
+        resolution_multiplier,
+        odd: true,
+    }
+}
+
 pub fn init_domain_elements(domain_area: &Area) -> Vec<Vec<DomainElement>> {
     let mut vy: Vec<Vec<DomainElement>> = Vec::new();
     for x in 0..domain_area.width_x {
         let mut vx: Vec<DomainElement> = Vec::new();
         for y in 0..domain_area.height_y {
             vx.push(domain_element::init(
-                domain_area.screen_to_domain_re(x),
-                domain_area.screen_to_domain_im(y),
+                domain_area.screen_to_domain_re(x, y),
+                domain_area.screen_to_domain_im(x, y),
             ));
         }
         vy.push(vx);
     }
     vy
 }
+
+#[test]
+fn test_recalculate_pixels_positions_for_this_zoom() {
+    use rusty_fractals_common::area;
+
+    let mut area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 100, height_y: 100, rotation: 0.0 });
+    let mut domain = init(&area, ResolutionMultiplier::None);
+    domain.domain_elements[50][50].set_finished_state(DomainElementState::FinishedSuccess);
+    let (re, im) = (domain.domain_elements[50][50].origin_re, domain.domain_elements[50][50].origin_im);
+
+    area.zoom_in();
+    domain.recalculate_pixels_positions_for_this_zoom(&area, true);
+
+    assert_eq!(domain.domain_elements.len(), 100);
+    assert_eq!(domain.domain_elements[0].len(), 100);
+    let (px, py) = area.domain_point_to_result_pixel(re, im);
+    let moved = &domain.domain_elements[px][py];
+    assert_eq!(moved.origin_re, re);
+    assert_eq!(moved.origin_im, im);
+    assert!(moved.is_finished_success_past());
+    // elements at the border moved out, new elements created there
+    assert!(domain.domain_elements[0][0].is_active_new());
+    assert_eq!(domain.domain_elements[0][0].origin_re, area.screen_to_domain_re(0, 0));
+}
+
+#[test]
+fn test_domain_element_states_to_image() {
+    use rusty_fractals_common::area;
+
+    let area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 10, height_y: 8, rotation: 0.0 });
+    let domain = init(&area, ResolutionMultiplier::None);
+    domain.domain_elements[9][7].set_finished_state(DomainElementState::FinishedSuccess);
+
+    let domain_image = domain.domain_element_states_to_image();
+
+    assert_eq!(domain_image.dimensions(), (10, 8));
+    assert_eq!(domain_image.get_pixel(9, 7), &FINISHED_SUCCESS);
+    assert_eq!(domain_image.get_pixel(9, 0), &ACTIVE_NEW);
+}
+
+#[test]
+fn test_shift() {
+    use rusty_fractals_common::area;
+
+    let mut area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.5, center_im: 0.25, width_x: 100, height_y: 100, rotation: 0.0 });
+    let mut domain = init(&area, ResolutionMultiplier::None);
+    let [re, im] = area.rebase();
+    domain.shift(re, im);
+
+    assert_eq!(domain.domain_elements[30][70].origin_re, area.screen_to_domain_re(30, 70));
+    assert_eq!(domain.domain_elements[30][70].origin_im, area.screen_to_domain_im(30, 70));
+}
+
+#[test]
+fn test_wrap() {
+    use rusty_fractals_common::area;
+
+    let area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 10, height_y: 10, rotation: 0.0 });
+    let el = active_new(0.0, 0.0);
+
+    let mut domain = init(&area, ResolutionMultiplier::Square3);
+    let wrapped = domain.wrap(&el, &area);
+    assert_eq!(wrapped.len(), 8);
+    assert!(wrapped.iter().all(|e| e.is_active_new()));
+
+    domain.resolution_multiplier = ResolutionMultiplier::Square5;
+    assert_eq!(domain.wrap(&el, &area).len(), 24);
+
+    domain.resolution_multiplier = SquareAlter;
+    let odd = domain.wrap(&el, &area);
+    domain.odd = false;
+    let even = domain.wrap(&el, &area);
+    assert_eq!(odd.len(), 2);
+    assert_eq!(even.len(), 2);
+    assert!(odd[0].origin_re > 0.0 && odd[0].origin_im > 0.0);
+    assert!(even[0].origin_re < 0.0 && even[0].origin_im > 0.0);
+}
+
+#[test]
+fn test_wrap_rotated() {
+    use rusty_fractals_common::area;
+
+    let area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 10, height_y: 10, rotation: 0.7 });
+    let domain = init(&area, SquareAlter);
+    let el = &domain.domain_elements[3][6];
+
+    // wrapped origins stay on the diagonal of the rotated pixel
+    let wrapped = domain.wrap(el, &area);
+    let (px, py) = area.domain_point_to_result_position(wrapped[0].origin_re, wrapped[0].origin_im);
+    assert!((px - (3.0 + 1.0 / 3.0)).abs() < 1e-9);
+    assert!((py - (6.0 + 1.0 / 3.0)).abs() < 1e-9);
+    let (px, py) = area.domain_point_to_result_position(wrapped[1].origin_re, wrapped[1].origin_im);
+    assert!((px - (3.0 - 1.0 / 3.0)).abs() < 1e-9);
+    assert!((py - (6.0 - 1.0 / 3.0)).abs() < 1e-9);
+}
+
+#[test]
+fn test_hibernate_mandelbrot_interior() {
+    use rusty_fractals_common::area;
+
+    let mut area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 100, height_y: 100, rotation: 0.0 });
+    let mut domain = init(&area, ResolutionMultiplier::None);
+    for column in domain.domain_elements.iter_mut() {
+        for el in column.iter_mut() {
+            el.set_finished_state(DomainElementState::FinishedTooLong);
+        }
+    }
+    // one good element
+    domain.domain_elements[20][50].set_finished_state(DomainElementState::FinishedSuccess);
+    let (re, im) = (domain.domain_elements[20][50].origin_re, domain.domain_elements[20][50].origin_im);
+
+    area.zoom_in();
+    let counts = domain.recalculate_pixels_positions_for_this_zoom(&area, true);
+    let (gx, gy) = area.domain_point_to_result_pixel(re, im);
+
+    let mut hibernated = 0;
+    for x in 0..domain.width {
+        for y in 0..domain.height {
+            let el = &domain.domain_elements[x][y];
+            if el.is_hibernated_deep_black() {
+                hibernated += 1;
+                // not next to the good element
+                assert!(x.abs_diff(gx) > NEIGHBOURS as usize || y.abs_diff(gy) > NEIGHBOURS as usize);
+            }
+        }
+    }
+    assert!(hibernated > 0);
+    assert_eq!(counts.hibernated, hibernated);
+    assert_eq!(counts.moved + counts.hibernated + counts.created, (domain.width * domain.height) as u64);
+}
+
+#[test]
+fn test_interior_stays_active_without_hibernation() {
+    use rusty_fractals_common::area;
+
+    let mut area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 100, height_y: 100, rotation: 0.0 });
+    let mut domain = init(&area, ResolutionMultiplier::None);
+    for el in domain.domain_elements.iter().flatten() {
+        el.set_finished_state(DomainElementState::FinishedTooLong);
+    }
+
+    // non-divergent paths are recorded from the interior
+    area.zoom_in();
+    let counts = domain.recalculate_pixels_positions_for_this_zoom(&area, false);
+
+    assert_eq!(counts.hibernated, 0);
+    assert!(counts.created > 0);
+    assert!(domain.domain_elements.iter().flatten().all(|el| !el.is_hibernated_deep_black()));
+}
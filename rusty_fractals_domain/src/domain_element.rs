@@ -1,51 +1,71 @@
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering::Relaxed;
+use crate::pixel_states;
 use crate::pixel_states::DomainElementState;
-use crate::pixel_states::DomainElementState::{ActiveNew, Finished, FinishedSuccess, FinishedSuccessPast, FinishedTooLong, FinishedTooShort, GoodPath, HibernatedDeepBlack};
+use crate::pixel_states::DomainElementState::{ActiveNew, FinishedSuccess, FinishedSuccessPast, FinishedTooLong, FinishedTooShort, GoodPath, HibernatedDeepBlack};
 
 pub struct DomainElement {
     pub origin_re: f64,
     pub origin_im: f64,
     // Element state is decided by calculation result. Alternatively: If all it's neighbours finished too long, it is going to be created as HibernatedBlack and its origin won't seed any calculation path.
-    pub state: DomainElementState,
+    // Atomic, because state is updated by the threads calculating domain chunks in parallel.
+    state: AtomicU8,
 }
 
 impl DomainElement {
+    pub fn state(&self) -> DomainElementState {
+        pixel_states::state_from(self.state.load(Relaxed))
+    }
+
+    fn set_state(&self, state: DomainElementState) {
+        self.state.store(state as u8, Relaxed);
+    }
+
     pub fn is_active_new(&self) -> bool {
-        self.state == ActiveNew
+        self.state() == ActiveNew
     }
 
     pub fn is_finished_too_short(&self) -> bool {
-        self.state == FinishedTooShort
+        self.state() == FinishedTooShort
+    }
+
+    pub fn is_finished_too_long(&self) -> bool {
+        self.state() == FinishedTooLong
+    }
+
+    pub fn is_hibernated_deep_black(&self) -> bool {
+        self.state() == HibernatedDeepBlack
     }
 
     pub fn is_hibernated(&self) -> bool {
-        self.state == FinishedTooShort || self.state == HibernatedDeepBlack
+        self.state() == FinishedTooShort || self.state() == HibernatedDeepBlack
     }
 
     pub fn is_finished_success_any(&self) -> bool {
-        self.state == FinishedSuccessPast || self.state == FinishedSuccess
+        self.state() == FinishedSuccessPast || self.state() == FinishedSuccess
     }
 
     pub fn is_finished_success_past(&self) -> bool {
-        self.state == FinishedSuccessPast
+        self.state() == FinishedSuccessPast
     }
 
 
-    pub fn set_finished_state(&mut self, state: DomainElementState) {
-        self.state = state;
+    pub fn set_finished_state(&self, state: DomainElementState) {
+        self.set_state(state);
     }
 
-    pub fn past(&mut self) {
-        if self.state == FinishedSuccess {
-            self.state = FinishedSuccessPast;
+    pub fn past(&self) {
+        if self.state() == FinishedSuccess {
+            self.set_state(FinishedSuccessPast);
         }
     }
 
-    pub fn has_worse_state_then(&self, e: DomainElement) -> bool {
-        self.state.cmp(&e.state).is_gt()
+    pub fn has_worse_state_then(&self, e: &DomainElement) -> bool {
+        self.state().cmp(&e.state()).is_gt()
     }
 
-    pub fn good_path(&mut self) {
-        self.state = GoodPath;
+    pub fn good_path(&self) {
+        self.set_state(GoodPath);
     }
 }
 
@@ -53,7 +73,7 @@ pub fn init(re: f64, im: f64) -> DomainElement {
     DomainElement {
         origin_re: re,
         origin_im: im,
-        state: ActiveNew,
+        state: AtomicU8::new(ActiveNew as u8),
     }
 }
 
@@ -61,7 +81,7 @@ pub fn active_new(re: f64, im: f64) -> DomainElement {
     DomainElement {
         origin_re: re,
         origin_im: im,
-        state: ActiveNew,
+        state: AtomicU8::new(ActiveNew as u8),
     }
 }
 
@@ -69,7 +89,7 @@ pub fn hibernated_deep_black(re: f64, im: f64) -> DomainElement {
     DomainElement {
         origin_re: re,
         origin_im: im,
-        state: HibernatedDeepBlack,
+        state: AtomicU8::new(HibernatedDeepBlack as u8),
     }
 }
 
@@ -78,5 +98,12 @@ pub fn hibernated_deep_black(re: f64, im: f64) -> DomainElement {
 pub fn is_active_new() {}
 
 #[test]
-pub fn set_finished_state() {}
+pub fn set_finished_state() {
+    let el = init(0.0, 0.0);
+    el.set_finished_state(FinishedSuccess);
+    assert_eq!(el.state(), FinishedSuccess);
+    el.past();
+    assert_eq!(el.state(), FinishedSuccessPast);
+    assert!(active_new(0.0, 0.0).has_worse_state_then(&el));
+}
 
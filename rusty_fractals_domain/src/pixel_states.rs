@@ -1,6 +1,5 @@
-use std::cmp::Ordering::Less;
 use image::Rgb;
-use crate::pixel_states::DomainElementState::{ActiveNew, Finished};
+use crate::pixel_states::DomainElementState::{ActiveNew, Finished, FinishedSuccess, FinishedSuccessPast, FinishedTooLong, FinishedTooShort, GoodPath, HibernatedDeepBlack};
 
 pub const ACTIVE_NEW: Rgb<u8> = Rgb([40, 180, 150]);
 pub const FINISHED_TOO_LONG: Rgb<u8> = Rgb([0, 0, 0]);
@@ -12,7 +11,7 @@ pub const FINISHED: Rgb<u8> = Rgb([130, 100, 130]); // TODO different color
 pub const GOOD_PATH: Rgb<u8> = Rgb([200, 108, 10]);
 
 
-#[derive(PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq)]
 pub enum DomainElementState {
     /**
      * 1.
@@ -76,7 +75,31 @@ pub enum DomainElementState {
     GoodPath,
 }
 
+// DomainElementState as stored by atomic DomainElement state
+pub fn state_from(value: u8) -> DomainElementState {
+    match value {
+        0 => FinishedSuccessPast,
+        1 => FinishedSuccess,
+        2 => Finished,
+        3 => ActiveNew,
+        4 => FinishedTooShort,
+        5 => FinishedTooLong,
+        6 => HibernatedDeepBlack,
+        _ => GoodPath,
+    }
+}
+
+#[test]
+fn test_state_from() {
+    for state in [FinishedSuccessPast, FinishedSuccess, Finished, ActiveNew, FinishedTooShort, FinishedTooLong, HibernatedDeepBlack, GoodPath] {
+        assert_eq!(state_from(state as u8), state);
+    }
+}
+
 #[test]
 fn test_pixel_state() {
-    assert_eq!(ActiveNew.cmp(&Finished), Less);
+    use std::cmp::Ordering::Less;
+
+    // finished element is better than new one, it wins conflict of elements moved to the same pixel
+    assert_eq!(Finished.cmp(&ActiveNew), Less);
 }
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy, PartialEq)]
 pub enum ResolutionMultiplier {
     // Single point at the center of Mandelbrot pixel
     None,
@@ -0,0 +1,148 @@
+// Colors of spectrum are interpolated in one of color spaces
+// Rgb: sRGB values directly, fast but uneven, middle colors are often dull
+// OkLab: perceptual lightness and hue change evenly
+// CieLch: CIELab lightness, chroma and hue, hue goes around the color wheel by the shorter way
+
+use std::f64::consts::PI;
+use image::Rgb;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation { Rgb, OkLab, CieLch }
+
+// color between from (f = 0) and to (f = 1)
+pub fn mix(from: Rgb<u8>, to: Rgb<u8>, f: f64, interpolation: Interpolation) -> Rgb<u8> {
+    match interpolation {
+        Interpolation::Rgb => {
+            let channel = |c: usize| (from[c] as f64 + (to[c] as f64 - from[c] as f64) * f).round() as u8;
+            Rgb([channel(0), channel(1), channel(2)])
+        }
+        Interpolation::OkLab => {
+            let (a, b) = (to_oklab(from), to_oklab(to));
+            from_oklab(lerp3(a, b, f))
+        }
+        Interpolation::CieLch => {
+            let (a, b) = (to_lch(from), to_lch(to));
+            // hue of gray is undefined, use hue of the other color
+            let ha = if a[1] < 1e-6 { b[2] } else { a[2] };
+            let hb = if b[1] < 1e-6 { ha } else { b[2] };
+            let mut dh = hb - ha;
+            if dh > PI {
+                dh -= 2.0 * PI;
+            } else if dh < -PI {
+                dh += 2.0 * PI;
+            }
+            from_lch([a[0] + (b[0] - a[0]) * f, a[1] + (b[1] - a[1]) * f, ha + dh * f])
+        }
+    }
+}
+
+fn lerp3(a: [f64; 3], b: [f64; 3], f: f64) -> [f64; 3] {
+    [a[0] + (b[0] - a[0]) * f, a[1] + (b[1] - a[1]) * f, a[2] + (b[2] - a[2]) * f]
+}
+
+fn to_linear(color: Rgb<u8>) -> [f64; 3] {
+    let channel = |v: u8| {
+        let c = v as f64 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    [channel(color[0]), channel(color[1]), channel(color[2])]
+}
+
+// out of gamut colors are clamped
+fn from_linear(rgb: [f64; 3]) -> Rgb<u8> {
+    let channel = |v: f64| {
+        let c = v.clamp(0.0, 1.0);
+        let s = if c <= 0.0031308 { 12.92 * c } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+        (s * 255.0).round() as u8
+    };
+    Rgb([channel(rgb[0]), channel(rgb[1]), channel(rgb[2])])
+}
+
+// https://bottosson.github.io/posts/oklab/
+fn to_oklab(color: Rgb<u8>) -> [f64; 3] {
+    let [r, g, b] = to_linear(color);
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+fn from_oklab(lab: [f64; 3]) -> Rgb<u8> {
+    let [ll, a, b] = lab;
+    let l = (ll + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (ll - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (ll - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    from_linear([
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ])
+}
+
+// CIE XYZ of D65 white point
+const WHITE: [f64; 3] = [0.95047, 1.0, 1.08883];
+const DELTA: f64 = 6.0 / 29.0;
+
+fn to_lch(color: Rgb<u8>) -> [f64; 3] {
+    let [r, g, b] = to_linear(color);
+    let xyz = [
+        0.4124564 * r + 0.3575761 * g + 0.1804375 * b,
+        0.2126729 * r + 0.7151522 * g + 0.0721750 * b,
+        0.0193339 * r + 0.1191920 * g + 0.9503041 * b,
+    ];
+    let f = |i: usize| {
+        let t = xyz[i] / WHITE[i];
+        if t > DELTA.powi(3) { t.cbrt() } else { t / (3.0 * DELTA * DELTA) + 4.0 / 29.0 }
+    };
+    let (fx, fy, fz) = (f(0), f(1), f(2));
+    let (l, a, b) = (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz));
+    [l, a.hypot(b), b.atan2(a)]
+}
+
+fn from_lch(lch: [f64; 3]) -> Rgb<u8> {
+    let [l, c, h] = lch;
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + c * h.cos() / 500.0;
+    let fz = fy - c * h.sin() / 200.0;
+    let f_inv = |t: f64| if t > DELTA { t.powi(3) } else { 3.0 * DELTA * DELTA * (t - 4.0 / 29.0) };
+    let (x, y, z) = (WHITE[0] * f_inv(fx), WHITE[1] * f_inv(fy), WHITE[2] * f_inv(fz));
+    from_linear([
+        3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+        -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+        0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+    ])
+}
+
+#[test]
+fn test_round_trip() {
+    for color in [Rgb([0, 0, 0]), Rgb([255, 255, 255]), Rgb([255, 0, 0]), Rgb([12, 200, 99]), Rgb([4, 13, 33])] {
+        assert_eq!(from_oklab(to_oklab(color)), color);
+        assert_eq!(from_lch(to_lch(color)), color);
+    }
+}
+
+#[test]
+fn test_mix() {
+    let black = Rgb([0, 0, 0]);
+    let white = Rgb([255, 255, 255]);
+    for interpolation in [Interpolation::Rgb, Interpolation::OkLab, Interpolation::CieLch] {
+        assert_eq!(mix(black, white, 0.0, interpolation), black);
+        assert_eq!(mix(black, white, 1.0, interpolation), white);
+    }
+    assert_eq!(mix(black, white, 0.5, Interpolation::Rgb), Rgb([128, 128, 128]));
+    // perceptual middle gray is lighter than sRGB middle
+    assert!(mix(black, white, 0.5, Interpolation::OkLab)[0] > 80);
+    let gray = mix(black, white, 0.5, Interpolation::CieLch);
+    assert!(gray[0] == gray[1] && gray[1] == gray[2]);
+}
+
+#[test]
+fn test_mix_hue() {
+    // red to blue goes through purple, not through gray
+    let purple = mix(Rgb([255, 0, 0]), Rgb([0, 0, 255]), 0.5, Interpolation::CieLch);
+    assert!(purple[0] > 100 && purple[2] > 100 && purple[1] < 50);
+}
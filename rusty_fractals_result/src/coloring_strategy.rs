@@ -0,0 +1,208 @@
+// Coloring strategies turn result pixel values to palette colors
+// - Perfect: histogram equalization, each color of spectrum is used by the same amount of pixels
+// - Log, SquareRoot, Gamma: value scaled by the function, relative to the most valuable pixel
+// - ClampedLinear: value relative to the value at percentile of non zero pixels, brighter pixels are clamped
+// Perfect coloring looks best for a single image, other strategies don't flicker between zoom frames
+
+use image::RgbImage;
+use rusty_fractals_common::constants::COLORING_THRESHOLD;
+use crate::palette::Palette;
+use crate::perfect_color_distribution::perfect_spectrum_indexes;
+use crate::result_pixels::ResultPixels;
+
+// Sync, result config with coloring strategy is shared by calculation threads
+pub trait ColoringStrategy: Send + Sync {
+    // palette color index for each pixel, indexes[x][y]
+    fn spectrum_indexes(&self, result_pixels: &ResultPixels, palette_color_count: usize) -> Vec<Vec<usize>>;
+
+    fn color(&self, result_pixels: &ResultPixels, palette: &Palette) -> RgbImage {
+        let indexes = self.spectrum_indexes(result_pixels, palette.spectrum.len());
+        RgbImage::from_fn(result_pixels.width as u32, result_pixels.height as u32, |x, y| {
+            palette.spectrum_value(indexes[x as usize][y as usize])
+        })
+    }
+}
+
+pub struct Perfect {}
+
+pub struct Log {}
+
+pub struct SquareRoot {}
+
+// position in spectrum is (value / max) ^ gamma, gamma less than 1 brightens dim pixels
+pub struct Gamma {
+    pub gamma: f64,
+}
+
+// percentile from 0.0 to 1.0, e.g. 0.99 ignores the brightest one percent of pixels
+pub struct ClampedLinear {
+    pub percentile: f64,
+}
+
+impl ColoringStrategy for Perfect {
+    fn spectrum_indexes(&self, result_pixels: &ResultPixels, palette_color_count: usize) -> Vec<Vec<usize>> {
+        perfect_spectrum_indexes(result_pixels, palette_color_count)
+    }
+}
+
+impl ColoringStrategy for Log {
+    fn spectrum_indexes(&self, result_pixels: &ResultPixels, palette_color_count: usize) -> Vec<Vec<usize>> {
+        let top = max_value(result_pixels);
+        scaled_spectrum_indexes(result_pixels, palette_color_count, |v| (1.0 + v).ln() / (1.0 + top).ln())
+    }
+}
+
+impl ColoringStrategy for SquareRoot {
+    fn spectrum_indexes(&self, result_pixels: &ResultPixels, palette_color_count: usize) -> Vec<Vec<usize>> {
+        let top = max_value(result_pixels);
+        scaled_spectrum_indexes(result_pixels, palette_color_count, |v| (v / top).sqrt())
+    }
+}
+
+impl ColoringStrategy for Gamma {
+    fn spectrum_indexes(&self, result_pixels: &ResultPixels, palette_color_count: usize) -> Vec<Vec<usize>> {
+        let top = max_value(result_pixels);
+        scaled_spectrum_indexes(result_pixels, palette_color_count, |v| (v / top).powf(self.gamma))
+    }
+}
+
+impl ColoringStrategy for ClampedLinear {
+    fn spectrum_indexes(&self, result_pixels: &ResultPixels, palette_color_count: usize) -> Vec<Vec<usize>> {
+        let top = percentile_value(result_pixels, self.percentile);
+        scaled_spectrum_indexes(result_pixels, palette_color_count, |v| v / top)
+    }
+}
+
+// scale maps pixel value to position in spectrum, from 0.0 to 1.0, it is called only for values above noise
+fn scaled_spectrum_indexes(result_pixels: &ResultPixels, palette_color_count: usize, scale: impl Fn(f64) -> f64) -> Vec<Vec<usize>> {
+    let last = palette_color_count.saturating_sub(1);
+    let mut indexes = vec![vec![0; result_pixels.height]; result_pixels.width];
+    for (x, column) in indexes.iter_mut().enumerate() {
+        for (y, index) in column.iter_mut().enumerate() {
+            let v = result_pixels.value_at(x, y);
+            if v > COLORING_THRESHOLD {
+                *index = (scale(v).clamp(0.0, 1.0) * last as f64).round() as usize;
+            }
+        }
+    }
+    indexes
+}
+
+fn max_value(result_pixels: &ResultPixels) -> f64 {
+    result_pixels.values().fold(0.0, f64::max)
+}
+
+// value of pixel at percentile of pixels above noise
+fn percentile_value(result_pixels: &ResultPixels, percentile: f64) -> f64 {
+    let mut values: Vec<f64> = result_pixels.values().filter(|v| *v > COLORING_THRESHOLD).collect();
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_unstable_by(f64::total_cmp);
+    let i = ((values.len() - 1) as f64 * percentile.clamp(0.0, 1.0)).round() as usize;
+    values[i]
+}
+
+// Nebulabrot, each layer is colored by its own palette
+// Colors of all layers are added together, there is at least one layer
+pub fn color_layers(coloring: &dyn ColoringStrategy, layers: &[ResultPixels], palettes: &[&Palette]) -> RgbImage {
+    println!("color_layers()");
+    let width = layers[0].width;
+    let height = layers[0].height;
+
+    let mut result_image = RgbImage::new(width as u32, height as u32);
+    for (layer, palette) in layers.iter().zip(palettes) {
+        let indexes = coloring.spectrum_indexes(layer, palette.spectrum.len());
+        for (x, column) in indexes.iter().enumerate() {
+            for (y, index) in column.iter().enumerate() {
+                let color = palette.spectrum_value(*index);
+                let pixel = result_image.get_pixel_mut(x as u32, y as u32);
+                for c in 0..3 {
+                    pixel[c] = pixel[c].saturating_add(color[c]);
+                }
+            }
+        }
+    }
+    result_image
+}
+
+#[cfg(test)]
+fn test_pixels() -> ResultPixels {
+    let result_pixels = crate::result_pixels::init(4, 1);
+    result_pixels.add_value(0, 0, 2.0);
+    result_pixels.add_value(1, 0, 10.0);
+    result_pixels.add_value(2, 0, 100.0);
+    result_pixels.add_value(3, 0, 1000.0);
+    result_pixels
+}
+
+#[test]
+fn test_log() {
+    let indexes = Log {}.spectrum_indexes(&test_pixels(), 11);
+    // noise
+    assert_eq!(indexes[0][0], 0);
+    assert_eq!(indexes[1][0], 3);
+    assert_eq!(indexes[2][0], 7);
+    assert_eq!(indexes[3][0], 10);
+}
+
+#[test]
+fn test_square_root() {
+    let indexes = SquareRoot {}.spectrum_indexes(&test_pixels(), 11);
+    assert_eq!(indexes[0][0], 0);
+    assert_eq!(indexes[1][0], 1);
+    assert_eq!(indexes[2][0], 3);
+    assert_eq!(indexes[3][0], 10);
+}
+
+#[test]
+fn test_gamma() {
+    // gamma 1 is linear
+    let indexes = Gamma { gamma: 1.0 }.spectrum_indexes(&test_pixels(), 11);
+    assert_eq!(indexes[1][0], 0);
+    assert_eq!(indexes[2][0], 1);
+    assert_eq!(indexes[3][0], 10);
+}
+
+#[test]
+fn test_clamped_linear() {
+    // 100 is in the middle of values above noise
+    let indexes = ClampedLinear { percentile: 0.5 }.spectrum_indexes(&test_pixels(), 11);
+    assert_eq!(indexes[1][0], 1);
+    assert_eq!(indexes[2][0], 10);
+    assert_eq!(indexes[3][0], 10);
+}
+
+#[test]
+fn test_color() {
+    use image::Rgb;
+
+    let palette = Palette { spectrum: vec![Rgb([0, 0, 0]), Rgb([100, 100, 100]), Rgb([200, 200, 200])] };
+    let image = SquareRoot {}.color(&test_pixels(), &palette);
+    assert_eq!(image.dimensions(), (4, 1));
+    assert_eq!(image.get_pixel(0, 0), &Rgb([0, 0, 0]));
+    assert_eq!(image.get_pixel(3, 0), &Rgb([200, 200, 200]));
+}
+
+#[test]
+fn test_color_layers() {
+    use image::Rgb;
+    use crate::result_pixels;
+
+    let short = result_pixels::init(4, 4);
+    let long = result_pixels::init(4, 4);
+    short.add_value(1, 2, 100.0);
+    short.add_value(3, 3, 200.0);
+    long.add_value(0, 1, 20.0);
+    long.add_value(3, 3, 50.0);
+    let red = Palette { spectrum: vec![Rgb([0, 0, 0]), Rgb([200, 0, 0])] };
+    let blue = Palette { spectrum: vec![Rgb([0, 0, 10]), Rgb([0, 0, 250])] };
+
+    let image = color_layers(&Perfect {}, &[short, long], &[&red, &blue]);
+
+    assert_eq!(image.dimensions(), (4, 4));
+    assert_eq!(image.get_pixel(0, 0), &Rgb([0, 0, 10]));
+    assert_eq!(image.get_pixel(1, 2), &Rgb([0, 0, 10]));
+    // the most valuable pixel of both layers
+    assert_eq!(image.get_pixel(3, 3), &Rgb([200, 0, 250]));
+}
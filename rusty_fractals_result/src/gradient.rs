@@ -0,0 +1,325 @@
+// Palettes defined by any amount of color stops
+// Each segment between two stops has its own Function curve
+// Gradients are saved as simple text files, one stop per line, with optional color space of interpolation
+//   interpolation OkLab
+//   # position red green blue function
+//   0.0 0 0 0 CircleUp
+//   0.7 255 0 0 Linear1
+//   1.0 255 255 255
+// or loaded from and saved to GIMP .ggr gradient files
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use image::Rgb;
+use crate::color_space;
+use crate::color_space::Interpolation;
+use crate::palette::Palette;
+use crate::palette_utils::function_result;
+use crate::palettes::Function;
+
+// position from 0.0 to 1.0
+// function defines the color change from this stop to the next one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+    pub position: f64,
+    pub color: Rgb<u8>,
+    pub function: Function,
+}
+
+// stops are ordered by position, there are at least two of them
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub stops: Vec<ColorStop>,
+    pub interpolation: Interpolation,
+}
+
+impl Gradient {
+    // length colors evenly distributed from the first stop to the last one
+    pub fn spectrum(&self, length: usize) -> Vec<Rgb<u8>> {
+        (0..length)
+            .map(|i| self.color_at(if length > 1 { i as f64 / (length - 1) as f64 } else { 0.0 }))
+            .collect()
+    }
+
+    pub fn palette(&self, length: usize) -> Palette {
+        Palette { spectrum: self.spectrum(length) }
+    }
+
+    fn color_at(&self, t: f64) -> Rgb<u8> {
+        let first = &self.stops[0];
+        if t <= first.position {
+            return first.color;
+        }
+        for segment in self.stops.windows(2) {
+            let (from, to) = (&segment[0], &segment[1]);
+            // stops at the same position make sharp color change
+            if t <= to.position && to.position > from.position {
+                let d = (t - from.position) / (to.position - from.position);
+                let f = function_result(d, &from.function).clamp(0.0, 1.0);
+                return color_space::mix(from.color, to.color, f, self.interpolation);
+            }
+        }
+        self.stops[self.stops.len() - 1].color
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("interpolation {:?}\n# position red green blue function\n", self.interpolation);
+        for stop in &self.stops {
+            let Rgb([r, g, b]) = stop.color;
+            text.push_str(&format!("{} {} {} {} {:?}\n", stop.position, r, g, b, stop.function));
+        }
+        text
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    // Each pair of stops is one GIMP segment with middle point in the center
+    // Curves which GIMP doesn't know are saved as linear
+    pub fn to_ggr(&self, name: &str) -> String {
+        let mut ggr = format!("GIMP Gradient\nName: {}\n{}\n", name, self.stops.len() - 1);
+        for segment in self.stops.windows(2) {
+            let (from, to) = (&segment[0], &segment[1]);
+            let blending = match from.function {
+                Function::Sine => 2,
+                Function::SphereIncreasing => 3,
+                Function::CircleUp => 4,
+                _ => 0,
+            };
+            ggr.push_str(&format!(
+                "{:.6} {:.6} {:.6} {} 1.000000 {} 1.000000 {} 0\n",
+                from.position, (from.position + to.position) / 2.0, to.position, ggr_color(from.color), ggr_color(to.color), blending
+            ));
+        }
+        ggr
+    }
+
+    // GIMP gradient is named by the file
+    pub fn save_ggr(&self, path: &Path) -> io::Result<()> {
+        let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or("Gradient");
+        fs::write(path, self.to_ggr(name))
+    }
+}
+
+// read text or .ggr gradient file, by extension
+pub fn load(path: &Path) -> io::Result<Gradient> {
+    let text = fs::read_to_string(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("ggr") => from_ggr(&text),
+        _ => from_text(&text),
+    }
+}
+
+pub fn load_palette(path: &Path, length: usize) -> io::Result<Palette> {
+    Ok(load(path)?.palette(length))
+}
+
+pub fn from_text(text: &str) -> io::Result<Gradient> {
+    let mut stops = Vec::new();
+    let mut interpolation = Interpolation::Rgb;
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words[0] == "interpolation" {
+            interpolation = match words.get(1) {
+                Some(&"Rgb") => Interpolation::Rgb,
+                Some(&"OkLab") => Interpolation::OkLab,
+                Some(&"CieLch") => Interpolation::CieLch,
+                _ => return Err(invalid(n, "unknown interpolation, expected Rgb, OkLab or CieLch")),
+            };
+            continue;
+        }
+        if words.len() < 4 || words.len() > 5 {
+            return Err(invalid(n, "expected: position red green blue [function]"));
+        }
+        let position = words[0].parse::<f64>().map_err(|_| invalid(n, "bad position"))?;
+        let mut color = [0; 3];
+        for (c, word) in color.iter_mut().zip(&words[1..4]) {
+            *c = word.parse::<u8>().map_err(|_| invalid(n, "bad color, expected 0 to 255"))?;
+        }
+        let function = match words.get(4) {
+            Some(name) => function_from_name(name).ok_or_else(|| invalid(n, "unknown function"))?,
+            None => Function::Linear1,
+        };
+        stops.push(ColorStop { position, color: Rgb(color), function });
+    }
+    Ok(Gradient { interpolation, ..init(stops)? })
+}
+
+// GIMP gradient, segments are
+//   left middle right, left color r g b a, right color r g b a, blending, coloring [left type, right type]
+// Segments with middle point out of the center are split in two linear segments
+// HSV coloring is read as RGB, alpha is ignored
+pub fn from_ggr(text: &str) -> io::Result<Gradient> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == "GIMP Gradient" => {}
+        _ => return Err(invalid(0, "not a GIMP gradient")),
+    }
+    let mut stops: Vec<ColorStop> = Vec::new();
+    for (n, line) in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        // name and amount of segments
+        if words.len() < 13 {
+            continue;
+        }
+        let mut v = [0.0; 13];
+        for (value, word) in v.iter_mut().zip(&words) {
+            *value = word.parse::<f64>().map_err(|_| invalid(n, "bad number"))?;
+        }
+        let (left, middle, right) = (v[0], v[1], v[2]);
+        let left_color = rgb_from_ggr(v[3], v[4], v[5]);
+        let right_color = rgb_from_ggr(v[7], v[8], v[9]);
+        let function = match v[11] as u32 {
+            0 => Function::Linear1,
+            // curved blending is position^(ln 0.5 / ln middle), it is linear with middle point in the center
+            // curved segment with middle point out of the center is approximated by the two linear segments below
+            1 => Function::Linear1,
+            2 => Function::Sine,
+            3 => Function::SphereIncreasing,
+            4 => Function::CircleUp,
+            _ => return Err(invalid(n, "unsupported blending")),
+        };
+
+        // previous segment ends with this stop, unless colors differ
+        if stops.last().is_some_and(|s| s.position == left && s.color == left_color) {
+            stops.pop();
+        }
+        if (middle - (left + right) / 2.0).abs() < 1e-6 {
+            stops.push(ColorStop { position: left, color: left_color, function });
+        } else {
+            stops.push(ColorStop { position: left, color: left_color, function: Function::Linear1 });
+            stops.push(ColorStop { position: middle, color: color_space::mix(left_color, right_color, 0.5, Interpolation::Rgb), function: Function::Linear1 });
+        }
+        stops.push(ColorStop { position: right, color: right_color, function: Function::Linear1 });
+    }
+    init(stops)
+}
+
+pub fn init(stops: Vec<ColorStop>) -> io::Result<Gradient> {
+    if stops.len() < 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "gradient needs at least two color stops"));
+    }
+    if stops.windows(2).any(|s| s[0].position > s[1].position) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "color stops are not ordered by position"));
+    }
+    Ok(Gradient { stops, interpolation: Interpolation::Rgb })
+}
+
+fn function_from_name(name: &str) -> Option<Function> {
+    match name {
+        "Linear1" => Some(Function::Linear1),
+        "Linear3" => Some(Function::Linear3),
+        "Linear7" => Some(Function::Linear7),
+        "Quadratic" => Some(Function::Quadratic),
+        "Exp" => Some(Function::Exp),
+        "Exp2" => Some(Function::Exp2),
+        "CircleDown" => Some(Function::CircleDown),
+        "CircleUp" => Some(Function::CircleUp),
+        "Sine" => Some(Function::Sine),
+        "SphereIncreasing" => Some(Function::SphereIncreasing),
+        _ => None,
+    }
+}
+
+fn rgb_from_ggr(r: f64, g: f64, b: f64) -> Rgb<u8> {
+    let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgb([channel(r), channel(g), channel(b)])
+}
+
+fn ggr_color(color: Rgb<u8>) -> String {
+    format!("{:.6} {:.6} {:.6}", color[0] as f64 / 255.0, color[1] as f64 / 255.0, color[2] as f64 / 255.0)
+}
+
+fn invalid(line: usize, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line + 1, message))
+}
+
+#[test]
+fn test_spectrum() {
+    let gradient = from_text("0.0 0 0 0\n0.5 200 0 0\n1.0 200 100 50 Linear1").unwrap();
+    let spectrum = gradient.spectrum(5);
+
+    assert_eq!(spectrum.len(), 5);
+    assert_eq!(spectrum[0], Rgb([0, 0, 0]));
+    assert_eq!(spectrum[1], Rgb([100, 0, 0]));
+    assert_eq!(spectrum[2], Rgb([200, 0, 0]));
+    assert_eq!(spectrum[3], Rgb([200, 50, 25]));
+    assert_eq!(spectrum[4], Rgb([200, 100, 50]));
+}
+
+#[test]
+fn test_spectrum_sharp_change() {
+    let gradient = from_text("0.0 0 0 0\n0.5 0 0 0\n0.5 255 255 255\n1.0 255 255 255").unwrap();
+    let spectrum = gradient.spectrum(3);
+
+    assert_eq!(spectrum[1], Rgb([0, 0, 0]));
+    assert_eq!(spectrum[2], Rgb([255, 255, 255]));
+}
+
+#[test]
+fn test_text() {
+    let gradient = init(vec![
+        ColorStop { position: 0.0, color: Rgb([1, 2, 3]), function: Function::CircleUp },
+        ColorStop { position: 0.25, color: Rgb([4, 5, 6]), function: Function::Quadratic },
+        ColorStop { position: 1.0, color: Rgb([7, 8, 9]), function: Function::Linear1 },
+    ]).unwrap();
+    let gradient = Gradient { interpolation: Interpolation::CieLch, ..gradient };
+
+    assert_eq!(from_text(&gradient.to_text()).unwrap(), gradient);
+    assert!(from_text("0.0 0 0 0").is_err());
+    assert!(from_text("0.0 0 0 0\n1.0 0 0 300").is_err());
+    assert!(from_text("0.5 0 0 0\n0.2 0 0 0").is_err());
+    assert!(from_text("0.0 0 0 0 Spiral\n1.0 0 0 0").is_err());
+    assert!(from_text("interpolation Hsv\n0.0 0 0 0\n1.0 0 0 0").is_err());
+}
+
+#[test]
+fn test_ggr() {
+    let ggr = "GIMP Gradient\n\
+        Name: Test\n\
+        2\n\
+        0.000000 0.250000 0.500000 0.000000 0.000000 0.000000 1.000000 1.000000 0.000000 0.000000 1.000000 4 0\n\
+        0.500000 0.600000 1.000000 1.000000 0.000000 0.000000 1.000000 1.000000 1.000000 1.000000 1.000000 0 0 0 0\n";
+
+    let gradient = from_ggr(ggr).unwrap();
+
+    // second segment is split by its middle point
+    assert_eq!(gradient.stops.len(), 4);
+    assert_eq!(gradient.stops[0], ColorStop { position: 0.0, color: Rgb([0, 0, 0]), function: Function::CircleUp });
+    assert_eq!(gradient.stops[1].color, Rgb([255, 0, 0]));
+    assert_eq!(gradient.stops[2].position, 0.6);
+    assert_eq!(gradient.stops[2].color, Rgb([255, 128, 128]));
+    assert_eq!(gradient.stops[3].color, Rgb([255, 255, 255]));
+
+    // written and read again
+    let saved = from_ggr(&gradient.to_ggr("Test")).unwrap();
+    assert_eq!(saved.stops.len(), 4);
+    assert_eq!(saved.stops[0].function, Function::CircleUp);
+    assert_eq!(saved.stops[3].color, Rgb([255, 255, 255]));
+    assert!(from_ggr("0.0 0 0 0").is_err());
+}
+
+#[test]
+fn test_load() {
+    let dir = std::env::temp_dir().join("rusty_fractals_test_gradient");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("test.palette");
+    let gradient = from_text("0.0 0 0 0\n1.0 255 255 255").unwrap();
+
+    gradient.save(&path).unwrap();
+
+    assert_eq!(load_palette(&path, 10).unwrap().spectrum.len(), 10);
+    assert!(load(&dir.join("missing.ggr")).is_err());
+
+    // loaded as GIMP gradient by extension
+    let ggr_path = dir.join("test.ggr");
+    gradient.save_ggr(&ggr_path).unwrap();
+    assert!(fs::read_to_string(&ggr_path).unwrap().starts_with("GIMP Gradient\nName: test\n"));
+    assert_eq!(load(&ggr_path).unwrap().stops, gradient.stops);
+}
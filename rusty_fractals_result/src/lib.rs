@@ -1,8 +1,11 @@
 pub mod palette;
 pub mod palettes;
 pub mod palette_utils;
+pub mod gradient;
+pub mod color_space;
 pub mod result_pixels;
 pub mod perfect_color_distribution;
+pub mod coloring_strategy;
 pub mod result_data;
 pub mod result_element_static;
 pub mod result_data_static;
@@ -2,6 +2,10 @@ use image::Rgb;
 
 const ERR_RED: Rgb<u8> = Rgb([255, 0, 0]);
 
+// enough distinct colors for perfect coloring of large images
+pub const SPECTRUM_LENGTH: usize = 4096;
+
+#[derive(Clone)]
 pub struct Palette {
     pub spectrum: Vec<Rgb<u8>>,
 }
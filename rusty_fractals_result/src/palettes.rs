@@ -1,8 +1,10 @@
-use crate::palette::Palette;
+use crate::color_space::Interpolation;
+use crate::palette::{Palette, SPECTRUM_LENGTH};
 use crate::palette::Palette3;
-use crate::palette_utils::make_spectrum;
+use crate::palette_utils::make_spectrum_in;
+use crate::gradient::{ColorStop, Gradient};
 use image::Rgb;
-use crate::palettes::Function::{CircleDown, CircleUp};
+use crate::palettes::Function::{CircleDown, CircleUp, Linear1, SphereIncreasing};
 
 const white: Rgb<u8> = Rgb([255, 255, 255]);
 const black: Rgb<u8> = Rgb([0, 0, 0]);
@@ -10,36 +12,86 @@ const red: Rgb<u8> = Rgb([255, 0, 0]);
 const green: Rgb<u8> = Rgb([0, 255, 0]);
 const blue: Rgb<u8> = Rgb([0, 0, 255]);
 
-pub enum Function { Linear1, Linear3, Linear7, Quadratic, Exp, Exp2, CircleDown, CircleUp }
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Function { Linear1, Linear3, Linear7, Quadratic, Exp, Exp2, CircleDown, CircleUp, Sine, SphereIncreasing }
 
 pub fn palette_black_to_white() -> Palette {
     Palette {
-        spectrum: make_spectrum(CircleUp, black, white)
+        spectrum: make_spectrum_in(CircleUp, black, white, SPECTRUM_LENGTH, Interpolation::OkLab)
     }
 }
 
 pub fn palette_blue_to_white() -> Palette {
     Palette {
-        spectrum: make_spectrum(CircleUp, Rgb([4, 13, 33]), Rgb([255, 255, 255]))
+        spectrum: make_spectrum_in(CircleUp, Rgb([4, 13, 33]), Rgb([255, 255, 255]), SPECTRUM_LENGTH, Interpolation::OkLab)
     }
 }
 
 pub fn palette_gray_to_blue() -> Palette {
     Palette {
-        spectrum: make_spectrum(CircleDown, Rgb([104, 113, 133]), Rgb([4, 13, 33]))
+        spectrum: make_spectrum_in(CircleDown, Rgb([104, 113, 133]), Rgb([4, 13, 33]), SPECTRUM_LENGTH, Interpolation::OkLab)
     }
 }
 
 pub fn palette_purple_to_white() -> Palette {
     Palette {
-        spectrum: make_spectrum(CircleUp, Rgb([20, 3, 30]), white)
+        spectrum: make_spectrum_in(CircleUp, Rgb([20, 3, 30]), white, SPECTRUM_LENGTH, Interpolation::OkLab)
     }
 }
 
+// insides of Mandelbrot set
+pub fn palette_black_to_gray() -> Palette {
+    Palette {
+        spectrum: make_spectrum_in(CircleUp, black, Rgb([100, 100, 100]), SPECTRUM_LENGTH, Interpolation::OkLab)
+    }
+}
+
+// Nebulabrot layers
+pub fn palette_black_to_red() -> Palette {
+    Palette {
+        spectrum: make_spectrum_in(CircleUp, black, red, SPECTRUM_LENGTH, Interpolation::OkLab)
+    }
+}
+
+pub fn palette_black_to_green() -> Palette {
+    Palette {
+        spectrum: make_spectrum_in(CircleUp, black, green, SPECTRUM_LENGTH, Interpolation::OkLab)
+    }
+}
+
+pub fn palette_black_to_blue() -> Palette {
+    Palette {
+        spectrum: make_spectrum_in(CircleUp, black, blue, SPECTRUM_LENGTH, Interpolation::OkLab)
+    }
+}
+
+// multiple color stops
+pub fn palette_fire() -> Palette {
+    Gradient {
+        stops: vec![
+            ColorStop { position: 0.0, color: black, function: CircleUp },
+            ColorStop { position: 0.4, color: Rgb([180, 20, 0]), function: Linear1 },
+            ColorStop { position: 0.8, color: Rgb([255, 200, 0]), function: SphereIncreasing },
+            ColorStop { position: 1.0, color: white, function: Linear1 },
+        ],
+        interpolation: Interpolation::OkLab,
+    }.palette(SPECTRUM_LENGTH)
+}
+
 pub fn palette_3_rgb() -> Palette3 {
     Palette3 {
-        spectrum_red: make_spectrum(CircleUp, black, red),
-        spectrum_green: make_spectrum(CircleUp, black, green),
-        spectrum_blue: make_spectrum(CircleUp, black, blue),
+        spectrum_red: make_spectrum_in(CircleUp, black, red, SPECTRUM_LENGTH, Interpolation::OkLab),
+        spectrum_green: make_spectrum_in(CircleUp, black, green, SPECTRUM_LENGTH, Interpolation::OkLab),
+        spectrum_blue: make_spectrum_in(CircleUp, black, blue, SPECTRUM_LENGTH, Interpolation::OkLab),
+    }
+}
+
+#[test]
+fn test_palettes_spectrum_length() {
+    let palettes = [palette_black_to_white(), palette_blue_to_white(), palette_gray_to_blue(), palette_purple_to_white(), palette_black_to_gray(), palette_black_to_red(), palette_fire()];
+    for palette in palettes {
+        assert_eq!(palette.spectrum.len(), SPECTRUM_LENGTH);
     }
+    let palette = palette_black_to_white();
+    assert_eq!((palette.spectrum[0], palette.spectrum[SPECTRUM_LENGTH - 1]), (black, white));
 }
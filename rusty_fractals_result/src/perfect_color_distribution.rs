@@ -8,14 +8,15 @@ use std::cmp::Ordering::Equal;
 use image::{Rgb, RgbImage};
 use constants::COLORING_THRESHOLD;
 use rusty_fractals_common::constants;
-use crate::palette::Palette;
+use crate::palette::{Palette, Palette3};
+use crate::result_data_static::ResultDataStatic;
 use crate::result_pixels::ResultPixels;
 
 // for Nebula like fractals
 struct Pix {
     x: usize,
     y: usize,
-    value: u32,
+    value: f64,
 }
 
 // for Mandelbrot like fractals
@@ -28,384 +29,301 @@ struct Mix {
 }
 
 pub fn perfectly_color_result_values(result_pixels: &ResultPixels, palette: &Palette) -> RgbImage {
-    let width = result_pixels.width;
-    let height = result_pixels.height;
-
-    // Result pixels, order by value
-    let mut pixels: Vec<Pix> = Vec::new();
-
-    let mut zero_value_elements = 0;
-
-    // read screen values
-    for y in 0..height - 1 {
-        for x in 0..width - 1 {
-            let v = result_pixels.value_at(x, y);
-            if v <= COLORING_THRESHOLD {
-                zero_value_elements += 1;
-            }
-            pixels.push(Pix { x, y, value: v });
-        }
-    }
+    let indexes = perfect_spectrum_indexes(result_pixels, palette.spectrum.len());
 
-    //  order pixels from the smallest to the highest value
-    pixels.sort_by(|first, second| first.value.cmp(&second.value));
+    let result_image = RgbImage::from_fn(result_pixels.width as u32, result_pixels.height as u32, |x, y| {
+        palette.spectrum_value(indexes[x as usize][y as usize])
+    });
 
-    let all_pixels_total: u32 = (width * height) as u32;
-    let all_pixels_non_zero: u32 = (all_pixels_total - zero_value_elements) as u32;
-    let palette_color_count: u32 = palette.spectrum.len() as u32;
-    let single_color_use: u32 = (all_pixels_non_zero as f64 / palette_color_count as f64) as u32;
-    let left: u32 = all_pixels_non_zero - (palette_color_count * single_color_use);
+    // Behold, the coloring is perfect
 
-    println!("------------------------------------");
-    println!("All pixels to paint:         {}", all_pixels_total);
-    println!("---------------------------> {}", (zero_value_elements + left + (single_color_use * palette_color_count)));
-    println!("Zero value pixels to paint:  {}", zero_value_elements);
-    println!("Non zero pixels to paint:    {}", all_pixels_non_zero);
-    println!("Spectrum, available colors:  {}", palette_color_count);
-    println!("Pixels per each color:       {}", single_color_use);
-    println!("left:                        {}", left);
-    println!("------------------------------------");
+    result_image
+}
 
-    let mut result_image = image::RgbImage::new(width as u32, height as u32);
+// Euler fractal, each of red, green and blue spectrum is perfectly colored separately
+pub fn perfectly_color_result_values_euler(pixels_red: &ResultPixels, pixels_green: &ResultPixels, pixels_blue: &ResultPixels, palette: &Palette3) -> RgbImage {
+    let width = pixels_red.width;
+    let height = pixels_red.height;
 
-    // paint mismatched pixel amount with the least value colour
-    let mut pi = 0;
-    for _ in 0..(left + zero_value_elements) {
-        let sp = pixels.get(pi).expect("pixels error");
-        pi += 1;
-        result_image.put_pixel(sp.x as u32, sp.y as u32, palette.spectrum_value(0));
-    }
+    let red = perfect_spectrum_indexes(pixels_red, palette.spectrum_red.len());
+    let green = perfect_spectrum_indexes(pixels_green, palette.spectrum_green.len());
+    let blue = perfect_spectrum_indexes(pixels_blue, palette.spectrum_blue.len());
 
-    // color all remaining pixels, these are order by value
-    for palette_colour_index in 0..palette_color_count - 1 {
-        for _ in 0..single_color_use - 1 {
-            // color all these pixels with same color
-            let sp = pixels.get(pi).expect("pixels error");
-            pi += 1;
-            if sp.value <= COLORING_THRESHOLD {
-                // color zero-value elements and low-value-noise with the darkest color
-                result_image.put_pixel(sp.x as u32, sp.y as u32, palette.spectrum_value(0));
-            } else {
-                // perfect-color all significant pixels
-                result_image.put_pixel(sp.x as u32, sp.y as u32, palette.spectrum_value(palette_colour_index as usize));
-            }
+    // read 3 euler spectra colors and write image colors
+    let mut result_image = RgbImage::new(width as u32, height as u32);
+    for y in 0..height {
+        for x in 0..width {
+            let r = palette.spectrum_value_red(red[x][y])[0];
+            let g = palette.spectrum_value_green(green[x][y])[1];
+            let b = palette.spectrum_value_blue(blue[x][y])[2];
+            result_image.put_pixel(x as u32, y as u32, Rgb([r, g, b]));
         }
     }
-    println!("painted:                   {}", pi);
 
     // Behold, the coloring is perfect
 
-    println!("clear pixels");
-    pixels.clear();
-
     result_image
 }
 
-/*
-fn perfectly_color_values_euler() -> RgbImage {
+// Spectrum index for each pixel
+// Pixels ordered by value are distributed evenly to all colors of spectrum
+pub(crate) fn perfect_spectrum_indexes(result_pixels: &ResultPixels, palette_color_count: usize) -> Vec<Vec<usize>> {
     let width = result_pixels.width;
     let height = result_pixels.height;
 
-    // Result pixels, order by value
-    let mut pixels_red: Vec<Pix> = Vec::new();
-    let mut pixels_green: Vec<Pix> = Vec::new();
-    let mut pixels_blue: Vec<Pix> = Vec::new();
-
-    let mut zero_value_elements_red = 0;
-    let mut zero_value_elements_green = 0;
-    let mut zero_value_elements_blue = 0;
-
-    // identify zero and low-value elements as zero or noise
-    let threshold = 1;
-
-    // read screen values
+    let mut pixels: Vec<Pix> = Vec::new();
+    let mut zero_value_elements = 0;
     for y in 0..height {
         for x in 0..width {
-            let r = result_pixels.value_at(x, y, red);
-            let g = result_pixels.value_at(x, y, green);
-            let b = result_pixels.value_at(x, y, blue);
-            if r <= threshold {
-                zero_value_elements_red += 1;
-            }
-            if g <= threshold {
-                zero_value_elements_green += 1;
-            }
-            if b <= threshold {
-                zero_value_elements_blue += 1;
+            let v = result_pixels.value_at(x, y);
+            if v <= COLORING_THRESHOLD {
+                zero_value_elements += 1;
             }
-            pixels_red.push(Pix { x, y, value: r });
-            pixels_green.push(Pix { x, y, value: g });
-            pixels_blue.push(Pix { x, y, value: b });
+            pixels.push(Pix { x, y, value: v });
         }
     }
 
     // order pixels from the smallest to the highest value
-    pixels_red.sort_by(|first, second| first.1.cmp(&second.1));
-    pixels_green.sort_by(|first, second| first.1.cmp(&second.1));
-    pixels_blue.sort_by(|first, second| first.1.cmp(&second.1));
-
-    let all_pixels_total : u32 = width * height;
-    let all_pixels_non_zero_red : u32 = all_pixels_total - zero_value_elements_red;
-    let all_pixels_non_zero_green : u32 = all_pixels_total - zero_value_elements_green;
-    let all_pixels_non_zero_blue : u32 = all_pixels_total - zero_value_elements_blue;
-    let palette_color_count : u32 = PaletteEuler3.colorResolution();
-    let single_color_use_red : u32 = all_pixels_non_zero_red / palette_color_count;
-    let single_color_use_green : u32 = all_pixels_non_zero_green / palette_color_count;
-    let single_color_use_blue : u32 = all_pixels_non_zero_blue / palette_color_count;
-    let left_red : u32 = all_pixels_non_zero_red - (palette_color_count * single_color_use_red);
-    let left_green : u32 = all_pixels_non_zero_green - (palette_color_count * single_color_use_green);
-    let left_blue : u32 = all_pixels_non_zero_blue - (palette_color_count * single_color_use_blue);
+    pixels.sort_by(|first, second| first.value.total_cmp(&second.value));
 
-    println!("------------------------------------");
-    println!("All pixels to paint:         {}", all_pixels_total);
-    println!("---------------------------> {}", (zero_value_elements_red + left_red + (single_color_use_red * palette_color_count)));
-    println!("---------------------------> {}", (zero_value_elements_green + left_green + (single_color_use_green * palette_color_count)));
-    println!("---------------------------> {}", (zero_value_elements_blue + left_blue + (single_color_use_blue * palette_color_count)));
-    println!("Zero value pixels to paint:  {}", zero_value_elements_red);
-    println!("Zero value pixels to paint:  {}", zero_value_elements_green);
-    println!("Zero value pixels to paint:  {}", zero_value_elements_blue);
-    println!("Non zero pixels to paint:    {}", all_pixels_non_zero_red);
-    println!("Non zero pixels to paint:    {}", all_pixels_non_zero_green);
-    println!("Non zero pixels to paint:    {}", all_pixels_non_zero_blue);
-    println!("Spectrum, available colors:  {}", palette_color_count);
-    println!("Pixels per each color:       {}", single_color_use_red);
-    println!("Pixels per each color:       {}", single_color_use_green);
-    println!("Pixels per each color:       {}", single_color_use_blue);
-    println!("left:                        {}", left_red);
-    println!("left:                        {}", left_green);
-    println!("left:                        {}", left_blue);
-    println!("------------------------------------");
-
-    // paint mismatched pixel amount with the least value colour
-    for pi_red in 0..(left_red + zero_value_elements_red) {
-        let sp = pixelsRed.get(pi_red);
-        PixelsEulerFinebrot.set(sp.x, sp.y, red, 0);
-    }
-    // color all remaining pixels, these are order by value
-    for palette_colour_index in 0..palette_color_count {
-        for _ in 0..single_color_use_red {
-            // color all these pixels with same color
-            let sp = pixels_red.get(pi_red += 1);
-            if sp.pixelValue() <= threshold {
-                PixelsEulerFinebrot.set(sp.x, sp.y, red, 0);
-            } else {
-                // perfect-color all significant pixels
-                PixelsEulerFinebrot.set(sp.x, sp.y, red, PaletteEuler3.getSpectrumValueRed(palette_colour_index).getRed());
-            }
-        }
-    }
-
-    for pi_green in 0..(leftGreen + zeroValueElementsGreen) {
-        sp = pixelsGreen.get(pi_green);
-        PixelsEulerFinebrot.set(sp.x, sp.y, green, 0);
-    }
-    // color all remaining pixels, these are order by value
-    for palette_colour_index in 0..palette_color_count {
-        for _ in 0..single_color_use_green {
-            // color all these pixels with same color
-            sp = pixelsGreen.get(pi_green += 1);
-            if sp.pixelValue() <= threshold {
-                // color zero-value elements and low-value-noise with the darkest color
-                PixelsEulerFinebrot.set(sp.x, sp.y, green, 0);
-            } else {
-                // perfect-color all significant pixels
-                PixelsEulerFinebrot.set(sp.x, sp.y, green, PaletteEuler3.getSpectrumValueGreen(palette_colour_index).getGreen());
-            }
-        }
-    }
+    let all_pixels_non_zero = width * height - zero_value_elements;
 
-    for pi_blue in 0..(leftBlue + zeroValueElementsBlue) {
-        sp = pixelsBlue.get(pi_blue);
-        PixelsEulerFinebrot.set(sp.x, sp.y, blue, 0);
-    }
-    // color all remaining pixels, these are order by value
-    for palette_colour_index in 0..palette_color_count {
-        for _ in 0..single_color_use_blue {
-            // color all these pixels with same color
-            sp = pixelsBlue.get(pi_blue += 1);
-            if sp.pixelValue() <= threshold {
-                // color zero-value elements and low-value-noise with the darkest color
-                PixelsEulerFinebrot.set(sp.x, sp.y, blue, 0);
-            } else {
-                // perfect-color all significant pixels
-                PixelsEulerFinebrot.set(sp.x, sp.y, blue, PaletteEuler3.getSpectrumValueBlue(palette_colour_index).getBlue());
-            }
-        }
+    // zero value pixels stay with the least value colour
+    let mut indexes = vec![vec![0; height]; width];
+    for (pi, sp) in pixels[zero_value_elements..].iter().enumerate() {
+        indexes[sp.x][sp.y] = perfect_index(pi, all_pixels_non_zero, palette_color_count);
     }
+    indexes
+}
 
-    // read 3 euler spectra colors and write image colors
-    for y in 0..height {
-        for x in 0..width {
-            let r = result_pixels.value_at(x, y, red);
-            let g = result_pixels.value_at(x, y, green);
-            let b = result_pixels.value_at(x, y, blue);
-            result_image.setRGB(x, y, Rgb([r, g, b]));
-        }
+// Spectrum index of pixel at position pi, of pixels ordered by value
+// Each color is used by the same amount of pixels, mismatched pixel amount gets the least value colour
+// Fewer pixels than colors, e.g. early frames, are spread over the spectrum up to its last color
+fn perfect_index(pi: usize, pixel_count: usize, palette_color_count: usize) -> usize {
+    if pixel_count < palette_color_count {
+        return (pi + 1) * (palette_color_count - 1) / pixel_count;
     }
-
-    // Behold, the coloring is perfect
-
-    println!("clear pixels");
-    pixels_red.clear();
-    pixels_green.clear();
-    pixels_blue.clear();
-    PixelsEulerFinebrot.clear();
-
-    result_image
+    let single_color_use = pixel_count / palette_color_count;
+    let left = pixel_count - (palette_color_count * single_color_use);
+    if pi < left { 0 } else { (pi - left) / single_color_use }
 }
-*/
 
 const NEIGHBOR_COORDINATES: [[i8; 2]; 8] = [[-1, -1], [0, -1], [1, -1], [-1, 0], [1, 0], [-1, 1], [0, 1], [1, 1]];
 
-/*
-fn perfectly_color_values_mandelbrot() -> RgbImage {
-    println!("perfectly_color_values()");
+// Mandelbrot like fractals
+// Pixels are perfectly colored by iteration count, pixels with the same value are ordered by inverse of quad
+// Insides of Mandelbrot set are perfectly colored by quad, with palette_zero
+pub fn perfectly_color_values_mandelbrot(result_data: &ResultDataStatic, palette: &Palette, palette_zero: Option<&Palette>) -> RgbImage {
+    println!("perfectly_color_values_mandelbrot()");
 
-    let width = result_pixels.width;
-    let height = result_pixels.height;
+    let width = result_data.width;
+    let height = result_data.height;
 
     // Result pixels, order by value
     let mut pixels: Vec<Mix> = Vec::new();
     let mut pixels_zero: Vec<Mix> = Vec::new();
 
-    let mut zero_value_elements = 0;
-
     // read screen values
-
     for y in 0..height {
         for x in 0..width {
-            let el = PixelsMandelbrot.elAt(x, y);
-            if el.value == 0 {
-                zero_value_elements += 1;
-                pixels_zero.add(MandelbrotPixelFactory.make(el, x, y));
+            let el = result_data.el_at(x, y);
+            let mp = Mix { x, y, value: el.value(), quad: el.quad(), quid: el.qiad() };
+            if el.value() == 0 {
+                pixels_zero.push(mp);
             } else {
-                MandelbrotPixel
-                mp = MandelbrotPixelFactory.make(el, x, y);
-                pixels.add(mp);
-                field[x][y] = mp;
+                pixels.push(mp);
             }
         }
     }
 
     //  order pixels from the smallest to the highest value
-    pixels.sort_by(|first, second| {
-        let c = first.value.cmp(&second.value);
-        if c == Equal {
-            first.quid.cmp(&second.quid)
-        }
-        c
-    });
-    pixels_zero.sort_by(|first, second| first.quad.cmp(&second.quad));
-    
-    let all_pixels_total : u32 = width * height;
-    let all_pixels_non_zero : u32 = all_pixels_total - zero_value_elements;
-    let palette_color_count : u32 = Palette.colorResolution();
-    let single_color_use : u32 = all_pixels_non_zero / palette_color_count;
+    pixels.sort_by(|first, second| first.value.cmp(&second.value).then(first.quid.total_cmp(&second.quid)));
+    pixels_zero.sort_by(|first, second| first.quad.total_cmp(&second.quad));
 
-    let left = all_pixels_non_zero - (palette_color_count * single_color_use);
+    let palette_color_count = palette.spectrum.len();
 
     println!("------------------------------------");
-    println!("All pixels to paint:         {}", all_pixels_total);
-    println!("---------------------------> {}", (zero_value_elements + left + (single_color_use * palette_color_count)));
-    println!("Zero value pixels to paint:  {}", zero_value_elements);
-    println!("Non zero pixels to paint:    {}", all_pixels_non_zero);
-    println!("Spectrum, available colors:> {}", palette_color_count);
-    println!("Pixels per each color:       {}", single_color_use);
-    println!("left:                        {}", left);
+    println!("All pixels to paint:         {}", width * height);
+    println!("Zero value pixels to paint:  {}", pixels_zero.len());
+    println!("Non zero pixels to paint:    {}", pixels.len());
+    println!("Spectrum, available colors:  {}", palette_color_count);
     println!("------------------------------------");
 
-    // paint mismatched pixel amount with the least but not the lowest value colour
-    while pi < left {
-        let mp = pixels.get(pi + +);
-        MandelbrotImage.setRGB(mp.px, mp.py, Palette.getSpectrumValue(0).getRGB());
-    }
-
-    let mut palette_colour_index = 0;
-    while palette_colour_index < palette_color_count {
-        for _ in 0..singleColorUse {
-            let mp = pixels.get(pi + +);
-            mp.colorValue(palette_colour_index);
-            MandelbrotImage.setRGB(mp.px, mp.py, Palette.getSpectrumValue(palette_colour_index).getRGB());
-        }
-        palette_colour_index += 1;
+    // palette colour index of each non zero pixel
+    let mut field: Vec<Vec<Option<usize>>> = vec![vec![None; height]; width];
+    for (pi, mp) in pixels.iter().enumerate() {
+        field[mp.x][mp.y] = Some(perfect_index(pi, pixels.len(), palette_color_count));
     }
 
-    Assert.assertEquals(pixels.size(), pi);
-
     // Fix black dots caused by quad inverse imperfection
     // Keep incorrect quad results
+    let black_dots: Vec<(&Mix, usize)> = pixels.iter()
+        .filter_map(|mp| ac_if_black_dot(mp, &field, result_data).map(|average_colour_index| (mp, average_colour_index)))
+        .collect();
+    println!("black dots fixed:            {}", black_dots.len());
+    for (mp, average_colour_index) in black_dots {
+        field[mp.x][mp.y] = Some(average_colour_index);
+    }
 
-    for mpp in pixels {
-        let average_colour_index = ac_if_black_dot(mpp);
-        if average_colour_index != -1 {
-            let mpp.colorValue(average_colour_index);
-            MandelbrotImage.setRGB(mpp.x, mpp.y, Palette.getSpectrumValue(average_colour_index).getRGB());
-        }
+    let mut result_image = RgbImage::new(width as u32, height as u32);
+    for mp in pixels.iter() {
+        let palette_colour_index = field[mp.x][mp.y].expect("colored pixel");
+        result_image.put_pixel(mp.x as u32, mp.y as u32, palette.spectrum_value(palette_colour_index));
     }
 
     // Paint insides of Mandelbrot set
 
-    let zero_palette_color_count = PaletteZero.colorResolution();
-    let zero_single_color_use = ((int)((double) zero_value_elements / (double) zero_palette_color_count));
-    let zero_left = zero_value_elements - (zero_palette_color_count * zero_single_color_use);
+    if let Some(palette_zero) = palette_zero {
+        let zero_palette_color_count = palette_zero.spectrum.len();
 
-    println!("zero_palette_color_count:    > {}", zero_palette_color_count);
-    println!("zero_single_color_use:       > {}", zero_single_color_use);
-    println!("zero_left:                   > {}", zero_left);
+        println!("zero_palette_color_count:    {}", zero_palette_color_count);
 
-    let piz;
-    for piz in 0..zeroLeft {
-        mp = pixels_zero.get(piz);
-        MandelbrotImage.setRGB(mp.px, mp.py, PaletteZero.getSpectrumValue(0).getRGB());
-    }
-    for zeropalette_colour_index in 0..zeropalette_color_count {
-        for _ in 0..zeroSingleColorUse {
-            // color all these pixels with same color
-            mp = pixels_zero.get(piz + +);
-            MandelbrotImage.setRGB(mp.px, mp.py, PaletteZero.getSpectrumValue(zeropalette_colour_index).getRGB());
+        for (piz, mp) in pixels_zero.iter().enumerate() {
+            let zero_palette_colour_index = perfect_index(piz, pixels_zero.len(), zero_palette_color_count);
+            result_image.put_pixel(mp.x as u32, mp.y as u32, palette_zero.spectrum_value(zero_palette_colour_index));
         }
     }
-
-    println!("painted:                   {}", pi);
+    // otherwise insides stay black
 
     // Behold, the coloring is perfect
 
-    println!("clear pixels");
-    pixels.clear();
-    pixels_zero.clear();
-
     result_image
 }
 
 // Return average color of neighbour elements
-fn ac_if_black_dot(MandelbrotPixel mp) -> i32 {
-    let pv = mp.pixelValue;
-    let sum = 0;
-    let neighbours = 0;
+fn ac_if_black_dot(mp: &Mix, field: &[Vec<Option<usize>>], result_data: &ResultDataStatic) -> Option<usize> {
+    let width = field.len() as i64;
+    let height = field[0].len() as i64;
+    let mut sum = 0;
+    let mut neighbours = 0;
     for c in NEIGHBOR_COORDINATES {
-        let a = mp.px + c[0];
-        let b = mp.py + c[1];
-        let n = check_domain(a, b);
-        if n != null {
-            if Math.abs(pv - n.pixelValue) > 2 {
-                // verify only one value difference gradient //
-                return -1;
-            }
-            sum += n.colorValue;
-            neighbours += 1;
-        } else {
-            // don't fix elements of edges 
-            -1
+        let a = mp.x as i64 + c[0] as i64;
+        let b = mp.y as i64 + c[1] as i64;
+        if a < 0 || b < 0 || a >= width || b >= height {
+            // don't fix elements of edges
+            return None;
         }
+        if mp.value.abs_diff(result_data.el_at(a as usize, b as usize).value()) > 2 {
+            // verify only one value difference gradient
+            return None;
+        }
+        // don't fix elements next to the insides of Mandelbrot set
+        let n = field[a as usize][b as usize]?;
+        sum += n;
+        neighbours += 1;
     }
 
-    let cv = mp.colorValue;
-    let average_value = (int)(sum / neighbours);
+    let cv = field[mp.x][mp.y]?;
+    let average_value = sum / neighbours;
 
-    if cv < average_value - 5 {
-        // darker 
-        average_value
+    if cv + 5 < average_value {
+        // darker
+        return Some(average_value);
     }
-    -1
+    None
+}
+
+#[test]
+fn test_perfectly_color_result_values_euler() {
+    use crate::palettes::palette_3_rgb;
+    use crate::result_pixels;
+
+    let red = result_pixels::init(4, 4);
+    let green = result_pixels::init(4, 4);
+    let blue = result_pixels::init(4, 4);
+    red.add_value(1, 2, 100.0);
+    blue.add_value(3, 0, 50.0);
+    blue.add_value(0, 0, 60.0);
+
+    let palette = palette_3_rgb();
+    let image = perfectly_color_result_values_euler(&red, &green, &blue, &palette);
+
+    assert_eq!(image.dimensions(), (4, 4));
+    assert_eq!(image.get_pixel(2, 2), &Rgb([palette.spectrum_red[0][0], palette.spectrum_green[0][1], palette.spectrum_blue[0][2]]));
+    // the most valuable pixel of each spectrum gets the brightest color, if the spectrum is not longer than non zero pixels
+    assert_eq!(perfect_spectrum_indexes(&red, 1)[1][2], 0);
+    assert_eq!(perfect_spectrum_indexes(&blue, 2)[0][0], 1);
+    assert_eq!(perfect_spectrum_indexes(&blue, 2)[3][0], 0);
+    assert_eq!(perfect_spectrum_indexes(&blue, 2)[1][1], 0);
+}
+
+#[test]
+fn test_perfect_spectrum_indexes_few_pixels() {
+    use crate::result_pixels;
+
+    let pixels = result_pixels::init(4, 4);
+    pixels.add_value(0, 1, 10.0);
+    pixels.add_value(2, 3, 30.0);
+    pixels.add_value(3, 3, 20.0);
+
+    let indexes = perfect_spectrum_indexes(&pixels, 4096);
+
+    assert_eq!(indexes[0][1], 1365);
+    assert_eq!(indexes[3][3], 2730);
+    assert_eq!(indexes[2][3], 4095);
+    assert_eq!(indexes[1][1], 0);
+}
+
+#[test]
+fn test_perfectly_color_values_mandelbrot_few_pixels() {
+    use crate::palettes::{palette_black_to_gray, palette_black_to_white};
+    use crate::result_data_static;
+
+    // fewer pixels than colors in both spectra
+    let mut result_data = result_data_static::init(3, 3);
+    result_data.pixels[0][0].set_finished_state(10, 100.0, 1000);
+    result_data.pixels[2][0].set_finished_state(20, 100.0, 1000);
+    result_data.pixels[2][2].set_finished_state(1000, 0.5, 1000);
+    let palette = palette_black_to_white();
+
+    let image = perfectly_color_values_mandelbrot(&result_data, &palette, Some(&palette_black_to_gray()));
+
+    assert_eq!(image.get_pixel(2, 0), &palette.spectrum[4095]);
+    assert_eq!(image.get_pixel(0, 0), &palette.spectrum[2047]);
+}
+
+#[test]
+fn test_perfectly_color_values_mandelbrot() {
+    use crate::result_data_static;
+
+    // one color for each of 24 non zero pixels
+    let palette = Palette { spectrum: (0..24).map(|i| Rgb([i * 10, i * 10, i * 10])).collect() };
+    let mut result_data = result_data_static::init(5, 5);
+    for x in 0..5 {
+        for y in 0..5 {
+            result_data.pixels[x][y].set_finished_state(10, 5.0 + (x * 5 + y) as f64, 100);
+        }
+    }
+    // black dot
+    result_data.pixels[2][2].set_finished_state(10, 1000.0, 100);
+    // Mandelbrot set
+    result_data.pixels[0][0].set_finished_state(100, 0.1, 100);
+
+    let image = perfectly_color_values_mandelbrot(&result_data, &palette, None);
+
+    assert_eq!(image.get_pixel(0, 0), &Rgb([0, 0, 0]));
+    // black dot colored by average of its neighbours
+    assert!(image.get_pixel(2, 2)[0] > 5);
+    // the furthest diverged pixel of the same value is the darkest
+    assert!(image.get_pixel(4, 4)[0] < image.get_pixel(4, 3)[0]);
+}
+
+#[test]
+fn test_perfectly_color_values_mandelbrot_value_edge() {
+    use crate::result_data_static;
+
+    let palette = Palette { spectrum: (0..25).map(|i| Rgb([i * 10, i * 10, i * 10])).collect() };
+    let mut result_data = result_data_static::init(5, 5);
+    for x in 0..5 {
+        for y in 0..5 {
+            result_data.pixels[x][y].set_finished_state(20, 5.0, 100);
+        }
+    }
+    // dark pixel of much lower value than its neighbours
+    result_data.pixels[2][2].set_finished_state(10, 5.0, 100);
+
+    let image = perfectly_color_values_mandelbrot(&result_data, &palette, None);
+
+    // correctly dark, not colored by average of its neighbours
+    assert_eq!(image.get_pixel(2, 2), &palette.spectrum[0]);
 }
-*/
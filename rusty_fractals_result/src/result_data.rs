@@ -7,21 +7,86 @@ pub struct ResultData {
     // As zoom progress, points [re,im] are projected to new pixels [px,py] until they migrate out of the the tiny result_rea.
     // Elements outside of tiny result_rea are removed. Very short PATHS are also removed.
     // All elements on (calculation) path are already inside displayed result_area because they are filtered like that during the calculation.
-    pub paths: Vec<Vec<[f64; 2]>>
+    pub paths: Vec<Vec<[f64; 2]>>,
+    // calculation length of each path, iteration count of its origin
+    // it is longer than the path, which holds only points inside of the area
+    pub iterations: Vec<u32>,
 }
 
 impl ResultData {
-    /*
-    pub fn remove_elements_outside(&mut self) {
+    // remove path points which migrated out of zoomed in result area, and paths which became too short
+    pub fn remove_elements_outside(&mut self, area_result: &Area) {
         println!("remove_elements_outside()");
-        for mut path in self.paths {
-            path.retain(|&el| self.area_result.contains(el[0], el[1]));
+        for path in self.paths.iter_mut() {
+            path.retain(|el| area_result.contains(el[0], el[1]));
         }
+        // keep iterations of remaining paths
+        let mut keep = self.paths.iter().map(|path| path.len() as u32 > constants::MINIMUM_PATH_LENGTH);
+        self.iterations.retain(|_| keep.next().unwrap_or(false));
         self.paths.retain(|path| path.len() as u32 > constants::MINIMUM_PATH_LENGTH);
     }
-    */
 
-    pub fn add_calculation_path(&mut self, path: Vec<[f64; 2]>) {
+    // area origin moved by [re, im], keep path points at the same place
+    pub fn shift(&mut self, re: f64, im: f64) {
+        for point in self.paths.iter_mut().flatten() {
+            point[0] -= re;
+            point[1] -= im;
+        }
+    }
+
+    pub fn add_calculation_path(&mut self, path: Vec<[f64; 2]>, iterator: u32) {
         self.paths.push(path);
+        self.iterations.push(iterator);
+    }
+
+    // merge calculation paths of other (thread local) result data into this one
+    pub fn merge(&mut self, mut other: ResultData) {
+        self.paths.append(&mut other.paths);
+        self.iterations.append(&mut other.iterations);
     }
-}
\ No newline at end of file
+}
+
+pub fn init() -> ResultData {
+    ResultData {
+        paths: Vec::new(),
+        iterations: Vec::new(),
+    }
+}
+
+#[test]
+fn test_merge() {
+    let mut result_data = init();
+    result_data.add_calculation_path(vec![[0.1, 0.2]], 10);
+    let mut other = init();
+    other.add_calculation_path(vec![[0.3, 0.4], [0.5, 0.6]], 20);
+
+    result_data.merge(other);
+
+    assert_eq!(result_data.paths.len(), 2);
+    assert_eq!(result_data.paths[1][1], [0.5, 0.6]);
+    assert_eq!(result_data.iterations, vec![10, 20]);
+}
+
+#[test]
+fn test_remove_elements_outside() {
+    use rusty_fractals_common::area;
+
+    let area = area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 10, height_y: 10, rotation: 0.0 });
+    let mut result_data = init();
+    result_data.add_calculation_path(vec![[0.1, 0.1], [0.2, 0.2], [0.3, 0.3], [0.4, 0.4], [0.45, 0.45], [0.7, 0.7]], 100);
+    result_data.add_calculation_path(vec![[0.1, 0.1], [0.2, 0.2], [0.3, 0.3], [0.4, 0.4], [0.7, 0.7], [0.8, 0.8]], 200);
+
+    result_data.remove_elements_outside(&area);
+
+    assert_eq!(result_data.paths.len(), 1);
+    assert_eq!(result_data.paths[0].len(), 5);
+    assert_eq!(result_data.iterations, vec![100]);
+}
+
+#[test]
+fn test_shift() {
+    let mut result_data = init();
+    result_data.add_calculation_path(vec![[0.5, 0.25], [1.0, -1.0]], 10);
+    result_data.shift(0.5, -1.0);
+    assert_eq!(result_data.paths[0], vec![[0.0, 1.25], [0.5, 0.0]]);
+}
@@ -1,18 +1,27 @@
-use rusty_fractals_common::area::Area;
+use crate::result_element_static;
 use crate::result_element_static::ResultElementStatic;
-use crate::result_pixels::ResultPixels;
 
-pub struct ResultData {
+// Calculation result of static Mandelbrot like fractals, one element for each pixel
+pub struct ResultDataStatic {
+    pub width: usize,
+    pub height: usize,
     pub pixels: Vec<Vec<ResultElementStatic>>,
-    pub area_result: Area,
 }
 
-impl ResultData {
-    pub fn remove_elements_outside(&mut self) {
-
+impl ResultDataStatic {
+    pub fn el_at(&self, x: usize, y: usize) -> &ResultElementStatic {
+        &self.pixels[x][y]
     }
+}
 
-    pub fn add_calculation_path_now(&mut self, path: Vec<[f64; 2]>) {
-
+pub fn init(width: usize, height: usize) -> ResultDataStatic {
+    let mut pixels = Vec::new();
+    for _ in 0..width {
+        let mut column = Vec::new();
+        for _ in 0..height {
+            column.push(result_element_static::init());
+        }
+        pixels.push(column);
     }
-}
\ No newline at end of file
+    ResultDataStatic { width, height, pixels }
+}
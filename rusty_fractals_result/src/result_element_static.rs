@@ -3,29 +3,33 @@ pub struct ResultElementStatic {
     value: u32,
     quad: f64,
     qiad: f64,
-    color_value: u32,
 }
 
 impl ResultElementStatic {
-    pub fn set_finished_state(&mut self, iterator: u32, q: f64) {
-        /*
+    // value 0 are insides of Mandelbrot set
+    pub fn set_finished_state(&mut self, iterator: u32, q: f64, iteration_max: u32) {
         self.quad = q;
+        // inverse of quad, Elements with same value, which diverged further, will be colored darker
+        self.qiad = 1.0 / q;
         if iterator < 1 {
             self.value = 1;
-        } else if iterator == ITERATION_MAX {
+        } else if iterator == iteration_max {
             self.value = 0;
         } else {
             self.value = iterator;
         }
-        */
     }
 
-    fn color_value(&self) -> u32 {
-        self.color_value
+    pub fn value(&self) -> u32 {
+        self.value
     }
 
-    fn set_color_value(&mut self, palette_colour_index: u32) {
-        self.color_value = palette_colour_index;
+    pub fn quad(&self) -> f64 {
+        self.quad
+    }
+
+    pub fn qiad(&self) -> f64 {
+        self.qiad
     }
 
     pub fn set_average_with(&mut self, e: ResultElementStatic) {
@@ -33,22 +37,38 @@ impl ResultElementStatic {
     }
 }
 
+pub fn init() -> ResultElementStatic {
+    ResultElementStatic {
+        value: 0,
+        quad: 0.0,
+        qiad: 0.0,
+    }
+}
+
+#[test]
+fn test_set_finished_state() {
+    let mut el = init();
+
+    el.set_finished_state(12, 8.0, 100);
+    assert_eq!(el.value(), 12);
+    assert_eq!(el.qiad(), 0.125);
+
+    el.set_finished_state(0, 8.0, 100);
+    assert_eq!(el.value(), 1);
+
+    el.set_finished_state(100, 0.5, 100);
+    assert_eq!(el.value(), 0);
+    assert_eq!(el.quad(), 0.5);
+}
 
 #[test]
 fn test_set_average_with() {
-    let mut me = ResultElementStatic {
-        value: 10,
-        quad: 0.0,
-        qiad: 0.0,
-        color_value: 0
-    };
-    let other = ResultElementStatic {
-        value: 3,
-        quad: 0.0,
-        qiad: 0.0,
-        color_value: 0
-    };
+    let mut me = init();
+    me.set_finished_state(10, 8.0, 100);
+    let mut other = init();
+    other.set_finished_state(3, 8.0, 100);
 
+    // rounded down
     me.set_average_with(other);
-    assert_eq!(me.value, 55);
+    assert_eq!(me.value(), 6);
 }
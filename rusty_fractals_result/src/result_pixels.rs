@@ -1,106 +1,305 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use rusty_fractals_common::area::Area;
+use rusty_fractals_common::constants::CHUNKS;
 
+// How is each path point added to pixels
+// Nearest: whole point to the nearest pixel
+// Bilinear: point is shared by four nearest pixels, by how much it overlaps them
+// Gaussian: point is spread over pixels within 3 sigma, sigma in pixels, Nearest if sigma isn't positive
+// Splatting smooths aliasing and moiré of zoom sequences
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Splatting { Nearest, Bilinear, Gaussian(f64) }
+
+// Pixel values are fixed point u64, there are VALUE_UNIT units in one path point
+// Fractions of splatted points are kept, and very long renders don't overflow
+const VALUE_UNIT: f64 = 65536.0;
+
+// Pixels are one flat buffer of atomic counters, x + y * width
+// Calculation threads add path points into the same result pixels at once, without locks
 pub struct ResultPixels {
     pub width: usize,
     pub height: usize,
-    pub pixels: Vec<Vec<u32>>,
+    pixels: Vec<AtomicU64>,
+    pub splatting: Splatting,
 }
 
 impl ResultPixels {
 
-    pub fn translate_paths_to_pixel_grid(&mut self, paths: Vec<Vec<[f64; 2]>>, area : &Area) {
+    // returns amount of added points
+    pub fn translate_paths_to_pixel_grid(&self, paths: &[Vec<[f64; 2]>], area : &Area) -> u64 {
         println!("translate_paths_to_pixel_grid()");
 
         let mut pixels_total = 0;
 
         for path in paths {
-            for re_im in path {
-                // translate [re,im] to [px,py]
-                let re = re_im[0];
-                let im = re_im[1];
-                if area.contains(re, im) {
-                    let (px, py) = area.domain_point_to_result_pixel(re, im);
+            pixels_total += self.translate_path_to_pixel_grid(path, area);
+        }
+        println!("pixels_total:   {}", pixels_total);
+        pixels_total
+    }
+
+    // returns amount of added points
+    pub fn translate_path_to_pixel_grid(&self, path: &[[f64; 2]], area: &Area) -> u64 {
+        let mut added = 0;
+        for re_im in path.iter() {
+            if self.add_point(re_im[0], re_im[1], area) {
+                added += 1;
+            }
+        }
+        added
+    }
+
+    // translate [re,im] to [px,py], false if the point isn't in the area
+    pub fn add_point(&self, re: f64, im: f64, area: &Area) -> bool {
+        if !area.contains(re, im) {
+            return false;
+        }
+        match self.splatting {
+            Splatting::Bilinear => {
+                let (fx, fy) = area.domain_point_to_result_position(re, im);
+                self.splat_bilinear(fx, fy);
+                true
+            }
+            Splatting::Gaussian(sigma) if sigma > 0.0 => {
+                let (fx, fy) = area.domain_point_to_result_position(re, im);
+                self.splat_gaussian(fx, fy, sigma);
+                true
+            }
+            // Gaussian without positive sigma has no kernel, the whole point goes to the nearest pixel
+            Splatting::Nearest | Splatting::Gaussian(_) => {
+                let (px, py) = area.domain_point_to_result_pixel(re, im);
+                // points at the very border round to the pixel next to the last one
+                if px < self.width && py < self.height {
                     self.add(px, py);
-                    pixels_total += 1;
+                    return true;
                 }
+                false
             }
         }
-        println!("pixels_total:   {}", pixels_total);
+    }
 
-        // remove elements which moved out of tiny area
-        // TODO self.remove_elements_outside();
+    pub fn add(&self, x: usize, y: usize) {
+        self.pixels[x + y * self.width].fetch_add(VALUE_UNIT as u64, Ordering::Relaxed);
+    }
 
-        // Stats.pathsTotalAmount = PATHS.size();
-        // Stats.pixelsValueTotal = pixels_total;
+    pub fn add_value(&self, x: usize, y: usize, value: f64) {
+        self.pixels[x + y * self.width].fetch_add((value * VALUE_UNIT).round() as u64, Ordering::Relaxed);
     }
 
-    pub fn add(&mut self, x: usize, y: usize) {
-        self.pixels[x][y] += 1;
+    // fraction of point, parts outside of the pixel grid are lost
+    fn add_weight(&self, x: i64, y: i64, weight: f64) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.add_value(x as usize, y as usize, weight);
+        }
     }
 
-    pub fn clear(&mut self) {
-        println!("clear");
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.pixels[x][y] = 0;
+    // pixel centers are at whole numbers
+    fn splat_bilinear(&self, fx: f64, fy: f64) {
+        let (x0, y0) = (fx.floor(), fy.floor());
+        let (tx, ty) = (fx - x0, fy - y0);
+        let (x, y) = (x0 as i64, y0 as i64);
+        self.add_weight(x, y, (1.0 - tx) * (1.0 - ty));
+        self.add_weight(x + 1, y, tx * (1.0 - ty));
+        self.add_weight(x, y + 1, (1.0 - tx) * ty);
+        self.add_weight(x + 1, y + 1, tx * ty);
+    }
+
+    // weights of kernel are normalized, the whole point is added
+    fn splat_gaussian(&self, fx: f64, fy: f64, sigma: f64) {
+        let radius = (3.0 * sigma).ceil().max(1.0) as i64;
+        let (cx, cy) = (fx.round() as i64, fy.round() as i64);
+        let weight = |x: i64, y: i64| {
+            let (dx, dy) = (x as f64 - fx, y as f64 - fy);
+            (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+        };
+        let mut sum = 0.0;
+        for x in cx - radius..=cx + radius {
+            for y in cy - radius..=cy + radius {
+                sum += weight(x, y);
+            }
+        }
+        for x in cx - radius..=cx + radius {
+            for y in cy - radius..=cy + radius {
+                self.add_weight(x, y, weight(x, y) / sum);
             }
         }
     }
 
-    pub fn value_at(&self, x: usize, y: usize) -> u32 {
-        self.pixels[x][y]
+    pub fn clear(&self) {
+        println!("clear");
+        for pixel in &self.pixels {
+            pixel.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn value_at(&self, x: usize, y: usize) -> f64 {
+        self.pixels[x + y * self.width].load(Ordering::Relaxed) as f64 / VALUE_UNIT
     }
 
-    pub fn best_four_chunks_value(&self) -> u32 {
+    // values of all pixels, in no particular order
+    pub fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.pixels.iter().map(|pixel| pixel.load(Ordering::Relaxed) as f64 / VALUE_UNIT)
+    }
+
+    // sum of four chunks with the most points, of CHUNKS x CHUNKS chunks as calculated by domain
+    // the last chunks take the remainder pixels, image smaller than CHUNKS pixels has empty chunks
+    pub fn best_four_chunks_value(&self) -> u64 {
         println!("best_four_chunks_value()");
-        let chunk_size_x = self.width / 20;
-        let chunk_size_y = self.height / 20;
-        let mut values: Vec<u32> = Vec::new();
-        for x in 0..19 {
-            for y in 0..19 {
+        let chunks = CHUNKS as usize;
+        let chunk_size_x = self.width / chunks;
+        let chunk_size_y = self.height / chunks;
+        let to = |chunk: usize, size: usize, total: usize| if chunk == chunks - 1 { total } else { (chunk + 1) * size };
+        let mut values: Vec<u64> = Vec::new();
+        for x in 0..chunks {
+            for y in 0..chunks {
                 values.push(self.chunk_value(
-                    x * chunk_size_x, (x + 1) * chunk_size_x,
-                    y * chunk_size_y, (y + 1) * chunk_size_y,
+                    x * chunk_size_x, to(x, chunk_size_x, self.width),
+                    y * chunk_size_y, to(y, chunk_size_y, self.height),
                 ));
             }
         }
         values.sort_by(|first, second| second.cmp(first));
 
-        let mut sum = 0;
-        for i in 0..4 {
-            let v = values.get(i);
-            match v {
-                Some(v) => sum += v,
-                None => panic!(),
-            }
-        }
+        let sum = values.iter().take(4).sum();
         println!("best_four_chunks_value() sum: {}", sum);
         sum
     }
 
-    fn chunk_value(&self, x_from: usize, x_to: usize, y_from: usize, y_to: usize) -> u32 {
-        let mut sum = 0;
+    fn chunk_value(&self, x_from: usize, x_to: usize, y_from: usize, y_to: usize) -> u64 {
+        let mut sum = 0.0;
         for x in x_from..x_to {
             for y in y_from..y_to {
-                sum += self.pixels[x][y];
+                sum += self.value_at(x, y);
             }
         }
-        sum
+        sum.round() as u64
     }
 }
 
 pub fn init(width: usize, height: usize) -> ResultPixels {
-    let mut vx = Vec::new();
-    for _ in 0..width - 1 {
-        let mut vy = Vec::new();
-        for _ in 0..height - 1 {
-            vy.push(0);
-        }
-        vx.push(vy);
-    }
     ResultPixels {
         width,
         height,
-        pixels: vx,
+        pixels: (0..width * height).map(|_| AtomicU64::new(0)).collect(),
+        splatting: Splatting::Nearest,
+    }
+}
+
+#[cfg(test)]
+fn test_area() -> Area {
+    use rusty_fractals_common::area;
+    area::init(area::AreaConfig { width_re: 1.0, center_re: 0.0, center_im: 0.0, width_x: 10, height_y: 10, rotation: 0.0 })
+}
+
+#[test]
+fn test_splat_nearest() {
+    let area = test_area();
+    let result_pixels = init(10, 10);
+    let added = result_pixels.translate_path_to_pixel_grid(&[[0.0, 0.0], [0.0, 0.0], [5.0, 0.0]], &area);
+    assert_eq!(added, 2);
+    let (px, py) = area.domain_point_to_result_pixel(0.0, 0.0);
+    assert_eq!(result_pixels.value_at(px, py), 2.0);
+}
+
+#[test]
+fn test_splat_bilinear() {
+    let area = test_area();
+    let result_pixels = ResultPixels { splatting: Splatting::Bilinear, ..init(10, 10) };
+    // half way between two pixel centers
+    let (fx, fy) = area.domain_point_to_result_position(0.0, 0.0);
+    let x = fx.floor() as usize;
+    let y = fy.round() as usize;
+    let re = area.screen_to_domain_re(x, y) + area.plank() / 2.0;
+    let (fx, _) = area.domain_point_to_result_position(re, area.screen_to_domain_im(x, y));
+    result_pixels.splat_bilinear(fx, y as f64);
+    assert!((result_pixels.value_at(x, y) - 0.5).abs() < 1e-9);
+    assert!((result_pixels.value_at(x + 1, y) - 0.5).abs() < 1e-9);
+    let sum: f64 = result_pixels.values().sum();
+    assert!((sum - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_splat_gaussian() {
+    let area = test_area();
+    let result_pixels = ResultPixels { splatting: Splatting::Gaussian(1.0), ..init(10, 10) };
+    result_pixels.translate_path_to_pixel_grid(&[[0.0, 0.0]], &area);
+    let sum: f64 = result_pixels.values().sum();
+    // each pixel rounds the fraction to VALUE_UNIT
+    assert!((sum - 1.0).abs() < 1e-3);
+    // the point is spread, the nearest pixel is the brightest
+    let (px, py) = area.domain_point_to_result_pixel(0.0, 0.0);
+    let max = result_pixels.values().fold(0.0, f64::max);
+    assert_eq!(result_pixels.value_at(px, py), max);
+    assert!(max < 1.0);
+
+    // part of point at the border is lost
+    result_pixels.clear();
+    result_pixels.splat_gaussian(0.0, 0.0, 1.0);
+    let sum: f64 = result_pixels.values().sum();
+    assert!(sum < 0.5);
+}
+
+#[test]
+fn test_splat_gaussian_without_sigma() {
+    let area = test_area();
+    let (px, py) = area.domain_point_to_result_pixel(0.0, 0.0);
+    for sigma in [0.0, -1.0, f64::NAN] {
+        let result_pixels = ResultPixels { splatting: Splatting::Gaussian(sigma), ..init(10, 10) };
+        assert!(result_pixels.add_point(0.0, 0.0, &area));
+        // the whole point in the nearest pixel, no NaN weights
+        assert_eq!(result_pixels.value_at(px, py), 1.0);
+        assert_eq!(result_pixels.values().sum::<f64>(), 1.0);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_add_concurrently() {
+    use std::thread;
+
+    let result_pixels = init(4, 3);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..1000 {
+                    result_pixels.add(3, 2);
+                    result_pixels.add_value(0, 1, 0.25);
+                }
+            });
+        }
+    });
+    assert_eq!(result_pixels.value_at(3, 2), 4000.0);
+    assert_eq!(result_pixels.value_at(0, 1), 1000.0);
+    assert_eq!(result_pixels.value_at(2, 2), 0.0);
+}
+
+#[test]
+fn test_value_beyond_u32() {
+    let result_pixels = init(1, 1);
+    result_pixels.add_value(0, 0, u32::MAX as f64);
+    result_pixels.add_value(0, 0, u32::MAX as f64);
+    assert_eq!(result_pixels.value_at(0, 0), 2.0 * u32::MAX as f64);
+}
+
+#[test]
+fn test_best_four_chunks_value_beyond_u32() {
+    let result_pixels = init(20, 20);
+    for x in 0..4 {
+        result_pixels.add_value(x, 0, u32::MAX as f64);
+    }
+    assert_eq!(result_pixels.best_four_chunks_value(), 4 * u32::MAX as u64);
+}
+
+#[test]
+fn test_best_four_chunks_value_whole_image() {
+    // the last chunk takes the remainder pixels
+    let result_pixels = init(45, 43);
+    result_pixels.add(44, 42);
+    result_pixels.add(38, 0);
+    assert_eq!(result_pixels.best_four_chunks_value(), 2);
+
+    // tiny image is all in the last chunk, other chunks are empty
+    let tiny = init(3, 3);
+    tiny.add(1, 1);
+    assert_eq!(tiny.best_four_chunks_value(), 1);
+    assert_eq!(init(0, 0).best_four_chunks_value(), 0);
+}